@@ -1,24 +1,41 @@
+use crate::backends::{default_backend, WindowBackend};
 use crate::classes::action::Action;
-use crate::classes::key_bindings::default_key_bindings;
-use crate::classes::keys::{Keys, VirtualKey};
-use crate::classes::native_monitor_container::NativeMonitorContainer;
+use crate::classes::gaps::{Gaps, MAX_GAP};
+use crate::classes::key_bindings::{conflicts, default_key_bindings, reserved};
+use crate::classes::keys::{Keys, Side, VirtualKey};
 use crate::classes::window_order::WindowOrder;
-use crate::layout_engines::LayoutEngineType;
+use crate::classes::window_rules::WindowRules;
+use crate::classes::workspace_layout::WorkspaceLayout;
+use crate::config::{Config, ConfigWatcher, CONFIG_POLL_INTERVAL};
+use crate::delegates::LayoutCompletedDelegate;
+use crate::ipc;
+use crate::ipc::events::EventFeed;
+use crate::ipc::protocol::{MonitorInfo, Query, Request, Response, WindowInfo, WorkspaceInfo};
+use crate::ipc::server::IpcServer;
+use crate::layout_engines::grid_layout_engine::FillOrder;
+use crate::layout_engines::{LayoutEngineType, Orientation};
+use crate::snapshot::{Snapshot, SNAPSHOT_FILE_NAME};
+use crate::window::Window;
 use crate::windows_manager::WindowsManager;
-use crate::workspace_container::WorkspaceContainer;
 use crate::workspace_manager::WorkspaceManager;
+use anyhow::{bail, Context, Result};
+use crossbeam_channel::Receiver;
 use eframe::egui;
 use eframe::emath::Align;
+use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use windows::Win32::Foundation::POINT;
-use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[derive(Deserialize, Serialize)]
 #[serde(default)]
 pub struct App {
     pub settings: Settings,
     pub key_bindings: HashMap<Action, Keys>,
+    pub passthrough: HashSet<Action>,
 
     #[serde(skip)]
     window_state: WindowState,
@@ -27,34 +44,99 @@ pub struct App {
     pub windows_manager: WindowsManager,
 
     #[serde(skip)]
-    pub monitor_container: NativeMonitorContainer,
+    pub workspace_manager: WorkspaceManager,
 
     #[serde(skip)]
-    pub workspace_manager: WorkspaceManager,
+    pub layout_completed: Option<Receiver<LayoutCompletedDelegate>>,
+
+    #[serde(skip)]
+    last_layout: Option<LayoutCompletedDelegate>,
+
+    #[serde(skip)]
+    config_path: Option<PathBuf>,
+
+    #[serde(skip)]
+    config_watcher: Option<ConfigWatcher>,
+
+    #[serde(skip)]
+    pub config_error: Option<String>,
+
+    // the action the next pressed chord gets bound to
+    #[serde(skip)]
+    pub recording: Option<Action>,
+
+    // actions without a binding, as their default keys were already taken
+    #[serde(skip)]
+    pub unbound: Vec<Action>,
+
+    #[serde(skip)]
+    ipc_server: Option<IpcServer>,
+
+    #[serde(skip)]
+    event_feed: Option<EventFeed>,
 
+    // where the last "Export state" went, or why it failed
     #[serde(skip)]
-    pub workspace_container: WorkspaceContainer,
+    exported: Option<String>,
 }
 
 impl Default for App {
     fn default() -> Self {
+        Self::with_backend(default_backend())
+    }
+}
+
+impl App {
+    pub fn with_backend(backend: Arc<dyn WindowBackend>) -> Self {
         Self {
             settings: Settings::default(),
             key_bindings: default_key_bindings(),
+            passthrough: HashSet::new(),
             window_state: WindowState::default(),
 
-            windows_manager: WindowsManager::default(),
-            monitor_container: NativeMonitorContainer::default(),
-            workspace_manager: WorkspaceManager::default(),
-            workspace_container: WorkspaceContainer::default(),
+            windows_manager: WindowsManager::new(backend.clone()),
+            workspace_manager: WorkspaceManager::new(backend),
+            layout_completed: None,
+            last_layout: None,
+
+            config_path: None,
+            config_watcher: None,
+            config_error: None,
+            recording: None,
+            unbound: Vec::new(),
+            ipc_server: None,
+            event_feed: None,
+            exported: None,
         }
     }
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Settings {
-    pub layout_engine_type: LayoutEngineType, // TODO Should be in workspace
+    pub panel_orientation: Orientation,
+    pub grid_fill_order: FillOrder,
+    pub grid_columns: usize, // 0 is automatic
+    pub gaps: Gaps,
     pub new_window_order: WindowOrder,
+    pub workspace_names: Vec<String>,
+    pub workspace_layouts: BTreeMap<String, WorkspaceLayout>,
+    pub window_rules: WindowRules,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            panel_orientation: Orientation::default(),
+            grid_fill_order: FillOrder::default(),
+            grid_columns: 0,
+            gaps: Gaps::default(),
+            new_window_order: WindowOrder::default(),
+            workspace_names: (1..=9).map(|i| i.to_string()).collect(),
+            workspace_layouts: BTreeMap::new(),
+            window_rules: WindowRules::default(),
+        }
+    }
 }
 
 #[derive(Default)]
@@ -64,11 +146,495 @@ struct WindowState {
 
 impl App {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        let mut app: App = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+
+        app.bind_missing_actions();
+        app.with_config_path(Config::path())
+            .with_ipc_path(ipc::client::path())
+    }
+
+    // the config file wins over eframe's storage, and is created from it when missing
+    pub fn with_config_path(mut self, path: Option<PathBuf>) -> Self {
+        self.config_path = path;
+
+        match &self.config_path {
+            // a broken file is shown in the settings, and the ones stored by eframe are used
+            Some(path) if path.exists() => {
+                let _ = self.load_config();
+            }
+            Some(_) => self.save_config(),
+            None => {}
         }
 
-        Default::default()
+        self.config_watcher = self
+            .config_path
+            .clone()
+            .map(|path| ConfigWatcher::new(path, CONFIG_POLL_INTERVAL));
+
+        self
+    }
+
+    // other programs can still drive the app through the keys and the window when the
+    // server fails to start
+    pub fn with_ipc_path(mut self, path: PathBuf) -> Self {
+        match IpcServer::start(path) {
+            Ok(ipc_server) => {
+                self.ipc_server = Some(ipc_server);
+                self.event_feed = Some(EventFeed::new(
+                    &mut self.windows_manager,
+                    &mut self.workspace_manager,
+                ));
+            }
+            Err(e) => error!("{:#}", e),
+        }
+
+        self
+    }
+
+    // bind actions added since the settings were saved, unless their default keys are taken
+    fn bind_missing_actions(&mut self) {
+        let mut defaults: Vec<_> = default_key_bindings().into_iter().collect();
+        defaults.sort();
+
+        self.unbound.clear();
+
+        for (action, keys) in defaults {
+            let Entry::Vacant(entry) = self.key_bindings.entry(action) else {
+                continue;
+            };
+            entry.insert(keys);
+
+            let taken = conflicts(&self.key_bindings)
+                .into_iter()
+                .find_map(|pair| match pair {
+                    (first, other) | (other, first) if first == action => Some(other),
+                    _ => None,
+                });
+
+            if let Some(other) = taken {
+                warn!("{} is unbound, {} is taken by {}", action, keys, other);
+                self.key_bindings.remove(&action);
+                self.unbound.push(action);
+            }
+        }
+    }
+
+    // reads the config file, the current settings stay when it's invalid
+    fn load_config(&mut self) -> Result<()> {
+        let Some(path) = &self.config_path else {
+            bail!("There's no config file");
+        };
+
+        match Config::load(path) {
+            Ok(config) => {
+                info!("Loaded config: {}", path.display());
+                self.key_bindings = config.key_bindings();
+                self.passthrough = config.passthrough();
+                self.settings = config.settings;
+                self.bind_missing_actions();
+                self.config_error = None;
+                Ok(())
+            }
+            Err(e) => {
+                error!("{:#}", e);
+                self.config_error = Some(format!("{:#}", e));
+                Err(e)
+            }
+        }
+    }
+
+    pub fn save_config(&mut self) {
+        let Some(path) = &self.config_path else {
+            return;
+        };
+
+        match self.config().save(path) {
+            Ok(()) => {
+                info!("Saved config: {}", path.display());
+                self.config_error = None;
+
+                if let Some(config_watcher) = &mut self.config_watcher {
+                    config_watcher.refresh();
+                }
+            }
+            Err(e) => {
+                error!("{:#}", e);
+                self.config_error = Some(format!("{:#}", e));
+            }
+        }
+    }
+
+    pub fn config(&self) -> Config {
+        Config::new(&self.settings, &self.key_bindings, &self.passthrough)
+    }
+
+    // applies what changed in the config file, leaving the windows where they are
+    pub fn reload_config(&mut self) -> Result<()> {
+        let previous = self.settings.clone();
+
+        self.load_config()?;
+        self.apply_changed_settings(&previous);
+        Ok(())
+    }
+
+    pub fn poll_config(&mut self) {
+        if self
+            .config_watcher
+            .as_mut()
+            .is_some_and(|config_watcher| config_watcher.poll())
+        {
+            // the error is kept in `config_error` for the settings to show
+            let _ = self.reload_config();
+        }
+    }
+
+    // pushes the settings down to the managers
+    pub fn apply_settings(&mut self) {
+        self.workspace_manager
+            .change_workspace_layouts(&self.settings.workspace_layouts);
+        self.workspace_manager
+            .change_panel_orientation(self.settings.panel_orientation);
+        self.workspace_manager
+            .change_grid(self.settings.grid_fill_order, self.settings.grid_columns);
+        self.workspace_manager.change_gaps(self.settings.gaps);
+        self.workspace_manager
+            .change_window_order(self.settings.new_window_order);
+        self.windows_manager
+            .change_window_rules(self.settings.window_rules.clone());
+        self.apply_key_bindings();
+    }
+
+    pub fn apply_key_bindings(&mut self) {
+        self.windows_manager
+            .change_key_bindings(&self.key_bindings, &self.passthrough);
+    }
+
+    // only pushes the settings that differ from `previous`, as every change re-creates or
+    // resets part of the layout
+    fn apply_changed_settings(&mut self, previous: &Settings) {
+        let settings = &self.settings;
+
+        if settings.workspace_names != previous.workspace_names {
+            warn!("Changed workspace names are used after a restart");
+        }
+
+        let layouts: BTreeMap<_, _> = settings
+            .workspace_layouts
+            .iter()
+            .filter(|(name, layout)| previous.workspace_layouts.get(*name) != Some(layout))
+            .map(|(name, layout)| (name.clone(), *layout))
+            .collect();
+        if !layouts.is_empty() {
+            self.workspace_manager.change_workspace_layouts(&layouts);
+        }
+
+        if settings.panel_orientation != previous.panel_orientation {
+            self.workspace_manager
+                .change_panel_orientation(settings.panel_orientation);
+        }
+
+        if settings.grid_fill_order != previous.grid_fill_order
+            || settings.grid_columns != previous.grid_columns
+        {
+            self.workspace_manager
+                .change_grid(settings.grid_fill_order, settings.grid_columns);
+        }
+
+        if settings.gaps != previous.gaps {
+            self.workspace_manager.change_gaps(settings.gaps);
+        }
+
+        self.workspace_manager
+            .change_window_order(settings.new_window_order);
+
+        if settings.window_rules != previous.window_rules {
+            self.windows_manager
+                .change_window_rules(settings.window_rules.clone());
+        }
+
+        self.apply_key_bindings();
+    }
+
+    // acts on the next bound chord, or binds it to the action being recorded
+    pub fn handle_keys(&mut self) {
+        let Some(action) = self.recording else {
+            if let Some(action) = self.windows_manager.handle_keys(&self.key_bindings) {
+                self.handle_action(action);
+            }
+            return;
+        };
+
+        // modifiers go down before the key they're held for
+        let Some(keys) = self
+            .windows_manager
+            .poll_keys()
+            .filter(|keys| !keys.key.is_modifier())
+        else {
+            return;
+        };
+
+        // the sides are left for the bindings panel to pick
+        let keys = keys.either_side();
+        self.recording = None;
+
+        if keys
+            == (Keys {
+                key: VirtualKey::Esc,
+                ..Default::default()
+            })
+        {
+            info!("Cancelled recording {}", action);
+            return;
+        }
+
+        info!("Recorded {} for {}", keys, action);
+        self.key_bindings.insert(action, keys);
+        self.apply_key_bindings();
+        self.save_config();
+    }
+
+    // answers the requests that came in since the last frame
+    pub fn handle_ipc(&mut self) {
+        while let Some(pending) = self.ipc_server.as_ref().and_then(|server| server.poll()) {
+            let response = self.handle_request(pending.request.clone());
+            pending.reply(response);
+        }
+    }
+
+    pub fn handle_request(&mut self, request: Request) -> Response {
+        debug!("request: {:?}", request);
+
+        match request {
+            Request::Command(action) => {
+                let workspaces = self
+                    .workspace_manager
+                    .workspace_container
+                    .workspaces()
+                    .len();
+                let monitors = self.workspace_manager.monitor_container.monitors.len();
+
+                match action {
+                    Action::SwitchToWorkspace(index)
+                    | Action::MoveFocusedWindowToWorkspace(index)
+                        if index >= workspaces =>
+                    {
+                        Response::Error(format!("No workspace at index {}", index))
+                    }
+                    Action::MoveFocusedWindowToMonitor(index) if index >= monitors => {
+                        Response::Error(format!("No monitor at index {}", index))
+                    }
+                    Action::ReloadConfig => match self.reload_config() {
+                        Ok(()) => Response::Ok,
+                        Err(e) => Response::Error(format!("{:#}", e)),
+                    },
+                    _ => {
+                        self.handle_action(action);
+                        Response::Ok
+                    }
+                }
+            }
+            Request::Query(Query::Windows) => Response::Windows(
+                self.windows_manager
+                    .windows
+                    .values()
+                    .map(|window| self.window_info(window))
+                    .collect(),
+            ),
+            Request::Query(Query::FocusedWindow) => Response::FocusedWindow(
+                self.windows_manager
+                    .windows
+                    .values()
+                    .find(|window| window.is_focused())
+                    .map(|window| self.window_info(window)),
+            ),
+            Request::Query(Query::Workspaces) => {
+                let focused = self.workspace_manager.focused_workspace();
+                let container = &self.workspace_manager.workspace_container;
+
+                Response::Workspaces(
+                    container
+                        .workspaces()
+                        .iter()
+                        .enumerate()
+                        .map(|(index, workspace)| {
+                            let monitor = container
+                                .get_current_monitor_for_workspace(index)
+                                .map(|monitor| monitor.index);
+
+                            WorkspaceInfo::new(index, workspace, monitor, focused == Some(index))
+                        })
+                        .collect(),
+                )
+            }
+            // answered by the server itself, see `IpcServer`
+            Request::Subscribe(_) => {
+                Response::Error("Subscriptions are handled by the server".to_string())
+            }
+            Request::Query(Query::State) => Response::State(self.snapshot()),
+            Request::Query(Query::Monitors) => {
+                let monitor_container = &self.workspace_manager.monitor_container;
+                let container = &self.workspace_manager.workspace_container;
+
+                Response::Monitors(
+                    monitor_container
+                        .monitors
+                        .iter()
+                        .map(|monitor| {
+                            let workspace = container
+                                .get_workspace_for_monitor(monitor)
+                                .and_then(|index| container.get_workspace_at_index(index));
+
+                            MonitorInfo::new(
+                                monitor,
+                                workspace,
+                                monitor.index == monitor_container.focused_monitor,
+                            )
+                        })
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::new(&self.windows_manager, &self.workspace_manager)
+    }
+
+    // writes the snapshot next to the config file, or the working directory without one
+    pub fn export_state(&self) -> Result<PathBuf> {
+        let path = self
+            .config_path
+            .as_ref()
+            .and_then(|path| path.parent())
+            .unwrap_or(Path::new("."))
+            .join(SNAPSHOT_FILE_NAME);
+
+        let text = serde_json::to_string_pretty(&self.snapshot())?;
+        fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+
+        info!("Exported state: {}", path.display());
+        Ok(path)
+    }
+
+    // passes what happened since the last frame on to the IPC subscribers
+    pub fn broadcast_events(&mut self) {
+        let Some(mut event_feed) = self.event_feed.take() else {
+            return;
+        };
+
+        let messages = event_feed.poll(self);
+        self.event_feed = Some(event_feed);
+
+        if let Some(ipc_server) = &self.ipc_server {
+            for message in messages {
+                ipc_server.broadcast(message);
+            }
+        }
+    }
+
+    pub fn window_info(&self, window: &Window) -> WindowInfo {
+        let workspace = self
+            .workspace_manager
+            .workspace_for_window(window.handle)
+            .and_then(|index| {
+                self.workspace_manager
+                    .workspace_container
+                    .get_workspace_at_index(index)
+            });
+        let floating = self.windows_manager.floating.contains_key(&window.handle);
+
+        WindowInfo::new(window, workspace, floating)
+    }
+
+    // every action, whether bound to keys or not, is dispatched from here
+    pub fn handle_action(&mut self, action: Action) {
+        info!("action: {:?}", action);
+
+        match action {
+            Action::ToggleFocusedWindowTiling => {
+                self.windows_manager.toggle_focused_window_tiling();
+            }
+            Action::IncreaseGaps => {
+                self.settings.gaps.increase();
+                self.workspace_manager.change_gaps(self.settings.gaps);
+            }
+            Action::DecreaseGaps => {
+                self.settings.gaps.decrease();
+                self.workspace_manager.change_gaps(self.settings.gaps);
+            }
+            Action::ToggleGaps => {
+                self.settings.gaps.toggle();
+                self.workspace_manager.change_gaps(self.settings.gaps);
+            }
+            Action::CloseFocusedWindow => self.windows_manager.close_focused_window(),
+            Action::MoveFocusedWindowToMonitor(index) => {
+                let monitor = self
+                    .workspace_manager
+                    .monitor_container
+                    .get_monitor_at_index(index);
+
+                if let Some(monitor) = monitor {
+                    self.windows_manager
+                        .move_focused_window_to_area(monitor.screen.working_area());
+                }
+
+                self.workspace_manager.move_focused_window_to_monitor(index);
+            }
+            Action::SwitchToWorkspace(index) => self.workspace_manager.switch_to_workspace(index),
+            Action::MoveFocusedWindowToWorkspace(index) => {
+                self.workspace_manager
+                    .move_focused_window_to_workspace(index);
+            }
+            Action::ReloadConfig => {
+                let _ = self.reload_config();
+            }
+            _ => {
+                if let Some(workspace) = self.workspace_manager.focused_workspace_mut() {
+                    match action {
+                        Action::FocusNextWindow => workspace.focus_next_window(),
+                        Action::FocusPreviousWindow => workspace.focus_previous_window(),
+                        Action::FocusPrimaryWindow => workspace.focus_primary_window(),
+                        Action::FocusWindowInDirection(direction) => {
+                            workspace.focus_window_in_direction(direction)
+                        }
+                        Action::SwapFocusAndNextWindow => workspace.swap_focus_and_next_window(),
+                        Action::SwapFocusAndPreviousWindow => {
+                            workspace.swap_focus_and_previous_window()
+                        }
+                        Action::SwapFocusAndPrimaryWindow => {
+                            workspace.swap_focus_and_primary_window()
+                        }
+                        Action::NextLayoutEngine => workspace.next_layout_engine(),
+                        Action::PreviousLayoutEngine => workspace.previous_layout_engine(),
+                        Action::ShrinkPrimaryArea => workspace.shrink_primary_area(),
+                        Action::ExpandPrimaryArea => workspace.expand_primary_area(),
+                        Action::IncrementNumInPrimary => workspace.increment_num_in_primary(),
+                        Action::DecrementNumInPrimary => workspace.decrement_num_in_primary(),
+                        Action::ResetLayout => workspace.reset_layout(),
+                        _ => {}
+                    }
+
+                    let layout_action = matches!(
+                        action,
+                        Action::NextLayoutEngine
+                            | Action::PreviousLayoutEngine
+                            | Action::ShrinkPrimaryArea
+                            | Action::ExpandPrimaryArea
+                            | Action::IncrementNumInPrimary
+                            | Action::DecrementNumInPrimary
+                            | Action::ResetLayout
+                    );
+                    if layout_action {
+                        if let Some(event_feed) = &mut self.event_feed {
+                            event_feed.layout_action(&workspace.name);
+                        }
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -76,8 +642,18 @@ impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         ctx.request_repaint(); // TODO: Temp fix to keep loop going
 
+        self.poll_config();
         self.windows_manager.handle_window();
-        self.windows_manager.handle_keys(&self.key_bindings);
+        self.workspace_manager.handle_events();
+
+        if let Some(layout_completed) = &self.layout_completed {
+            if let Some(layout) = layout_completed.try_iter().last() {
+                self.last_layout = Some(layout);
+            }
+        }
+        self.handle_keys();
+        self.handle_ipc();
+        self.broadcast_events();
         self.windows_manager.handle_mouse();
 
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
@@ -99,28 +675,49 @@ impl eframe::App for App {
                     self.window_state.settings = !self.window_state.settings;
                 }
 
+                if ui
+                    .button("Export state")
+                    .on_hover_text("Save the monitors, workspaces and windows as JSON")
+                    .clicked()
+                {
+                    self.exported = Some(match self.export_state() {
+                        Ok(path) => format!("Exported to {}", path.display()),
+                        Err(e) => {
+                            error!("{:#}", e);
+                            format!("{:#}", e)
+                        }
+                    });
+                }
+
+                if let Some(exported) = &self.exported {
+                    ui.label(exported);
+                }
+
                 egui::warn_if_debug_build(ui);
             });
         });
 
+        let before = self.window_state.settings.then(|| self.config());
+
         egui::Window::new("Settings")
             .open(&mut self.window_state.settings)
             .show(ctx, |ui| {
+                if let Some(config_error) = &self.config_error {
+                    ui.colored_label(ui.visuals().error_fg_color, config_error);
+                }
+
                 egui::containers::collapsing_header::CollapsingHeader::new("Parameters")
                     .default_open(true)
                     .show(ui, |ui| {
                         ui.horizontal(|ui| {
-                            ui.heading("Layout");
+                            ui.heading("Panel");
                             ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
-                                let response = egui::ComboBox::new("layout_engine_type", "")
-                                    .selected_text(format!(
-                                        "{:?}",
-                                        self.settings.layout_engine_type
-                                    ))
+                                let response = egui::ComboBox::new("panel_orientation", "")
+                                    .selected_text(format!("{:?}", self.settings.panel_orientation))
                                     .show_ui(ui, |ui| {
-                                        for option in LayoutEngineType::variants() {
+                                        for option in Orientation::variants() {
                                             ui.selectable_value(
-                                                &mut self.settings.layout_engine_type,
+                                                &mut self.settings.panel_orientation,
                                                 option,
                                                 format!("{:?}", option),
                                             );
@@ -128,19 +725,224 @@ impl eframe::App for App {
                                     });
 
                                 if response.response.changed() {
-                                    self.windows_manager
-                                        .change_layout(self.settings.layout_engine_type);
+                                    self.workspace_manager
+                                        .change_panel_orientation(self.settings.panel_orientation);
+                                }
+                            });
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.heading("Grid");
+                            ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                                let columns = ui
+                                    .add(
+                                        egui::DragValue::new(&mut self.settings.grid_columns)
+                                            .clamp_range(0..=16)
+                                            .custom_formatter(|n, _| match n as usize {
+                                                0 => "Auto".to_string(),
+                                                n => n.to_string(),
+                                            }),
+                                    )
+                                    .on_hover_text("Columns");
+
+                                let response = egui::ComboBox::new("grid_fill_order", "")
+                                    .selected_text(format!("{:?}", self.settings.grid_fill_order))
+                                    .show_ui(ui, |ui| {
+                                        for option in FillOrder::variants() {
+                                            ui.selectable_value(
+                                                &mut self.settings.grid_fill_order,
+                                                option,
+                                                format!("{:?}", option),
+                                            );
+                                        }
+                                    });
+
+                                if response.response.changed() || columns.changed() {
+                                    self.workspace_manager.change_grid(
+                                        self.settings.grid_fill_order,
+                                        self.settings.grid_columns,
+                                    );
                                 }
                             });
                         });
                     });
 
+                egui::containers::collapsing_header::CollapsingHeader::new("Workspaces")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        let workspaces =
+                            self.workspace_manager.workspace_container.workspaces_mut();
+
+                        for workspace in workspaces {
+                            let mut layout = workspace.layout();
+                            let mut changed = false;
+
+                            ui.horizontal(|ui| {
+                                ui.heading(&workspace.name);
+                                ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                                    if let Some(percent) = &mut layout.primary_percent {
+                                        changed |= ui
+                                            .add(
+                                                egui::DragValue::new(percent)
+                                                    .clamp_range(0.1..=0.9)
+                                                    .speed(0.01),
+                                            )
+                                            .on_hover_text("Primary percent")
+                                            .changed();
+                                    }
+
+                                    if let Some(count) = &mut layout.num_in_primary {
+                                        changed |= ui
+                                            .add(egui::DragValue::new(count).clamp_range(1..=10))
+                                            .on_hover_text("Primary count")
+                                            .changed();
+                                    }
+
+                                    let response = egui::ComboBox::new(
+                                        format!("layout_{}", workspace.name),
+                                        "",
+                                    )
+                                    .selected_text(format!("{:?}", layout.layout_engine_type))
+                                    .show_ui(ui, |ui| {
+                                        for option in LayoutEngineType::variants() {
+                                            ui.selectable_value(
+                                                &mut layout.layout_engine_type,
+                                                option,
+                                                format!("{:?}", option),
+                                            );
+                                        }
+                                    });
+
+                                    changed |= response.response.changed();
+                                });
+                            });
+
+                            if changed {
+                                // a newly picked engine keeps its own primary area
+                                if layout.layout_engine_type != workspace.layout_engine {
+                                    layout.num_in_primary = None;
+                                    layout.primary_percent = None;
+                                }
+
+                                workspace.set_layout(layout);
+                                self.settings
+                                    .workspace_layouts
+                                    .insert(workspace.name.clone(), workspace.layout());
+                            }
+                        }
+                    });
+
+                egui::containers::collapsing_header::CollapsingHeader::new("Gaps")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        let gaps = &mut self.settings.gaps;
+                        let mut changed = ui.checkbox(&mut gaps.enabled, "Enabled").changed();
+
+                        ui.horizontal(|ui| {
+                            ui.label("Inner");
+                            changed |= ui
+                                .add(egui::DragValue::new(&mut gaps.inner).clamp_range(0..=MAX_GAP))
+                                .changed();
+                            ui.label("Step");
+                            changed |= ui
+                                .add(egui::DragValue::new(&mut gaps.step).clamp_range(1..=50))
+                                .changed();
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label("Outer");
+                            for (label, value) in [
+                                ("Left", &mut gaps.outer.left),
+                                ("Top", &mut gaps.outer.top),
+                                ("Right", &mut gaps.outer.right),
+                                ("Bottom", &mut gaps.outer.bottom),
+                            ] {
+                                ui.label(label);
+                                changed |= ui
+                                    .add(egui::DragValue::new(value).clamp_range(0..=MAX_GAP))
+                                    .changed();
+                            }
+                        });
+
+                        if changed {
+                            self.workspace_manager.change_gaps(self.settings.gaps);
+                        }
+                    });
+
                 egui::containers::collapsing_header::CollapsingHeader::new("Bindings")
                     .default_open(true)
                     .show(ui, |ui| {
-                        self.key_bindings.iter_mut().for_each(|(action, keys)| {
+                        let error_color = ui.visuals().error_fg_color;
+                        let warn_color = ui.visuals().warn_fg_color;
+
+                        let clashing = conflicts(&self.key_bindings);
+                        if !clashing.is_empty() {
+                            ui.colored_label(error_color, "Bindings sharing keys are not saved");
+                        }
+
+                        if !self.unbound.is_empty() {
+                            let actions: Vec<_> =
+                                self.unbound.iter().map(Action::to_string).collect();
+                            ui.colored_label(
+                                warn_color,
+                                format!(
+                                    "Unbound, their default keys are taken: {}",
+                                    actions.join(", ")
+                                ),
+                            );
+                        }
+
+                        let mut key_bindings: Vec<_> = self.key_bindings.iter_mut().collect();
+                        key_bindings.sort_by_key(|(action, _)| **action);
+
+                        key_bindings.into_iter().for_each(|(action, keys)| {
+                            let clashes: Vec<_> = clashing
+                                .iter()
+                                .filter_map(|(first, second)| match action {
+                                    _ if action == first => Some(second.to_string()),
+                                    _ if action == second => Some(first.to_string()),
+                                    _ => None,
+                                })
+                                .collect();
+
                             ui.separator();
-                            ui.heading(action.to_string());
+                            ui.horizontal(|ui| {
+                                let heading = egui::RichText::new(action.to_string());
+                                if clashes.is_empty() {
+                                    ui.heading(heading);
+                                } else {
+                                    ui.heading(heading.color(error_color));
+                                }
+
+                                ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                                    if self.recording == Some(*action) {
+                                        if ui.button("Cancel").clicked() {
+                                            self.recording = None;
+                                        }
+                                        ui.label("Press keys, Esc to cancel");
+                                    } else if ui
+                                        .button("Record")
+                                        .on_hover_text("Bind the next keys pressed")
+                                        .clicked()
+                                    {
+                                        self.recording = Some(*action);
+                                    }
+
+                                    let mut passthrough = self.passthrough.contains(action);
+
+                                    if ui
+                                        .checkbox(&mut passthrough, "Passthrough")
+                                        .on_hover_text("Also send the keys to the focused window")
+                                        .changed()
+                                    {
+                                        if passthrough {
+                                            self.passthrough.insert(*action);
+                                        } else {
+                                            self.passthrough.remove(action);
+                                        }
+                                    }
+                                });
+                            });
 
                             egui::ComboBox::new(format!("bindings_{:?}", action), "Key")
                                 .selected_text(format!("{:?}", keys.key))
@@ -155,16 +957,65 @@ impl eframe::App for App {
                                 });
 
                             ui.horizontal(|ui| {
-                                ui.checkbox(&mut keys.shift, "Shift");
-                                ui.checkbox(&mut keys.ctrl, "Ctrl");
-                                ui.checkbox(&mut keys.alt, "Alt");
-                                ui.checkbox(&mut keys.win, "Win");
+                                for (held, side, name) in [
+                                    (&mut keys.shift, &mut keys.shift_side, "Shift"),
+                                    (&mut keys.ctrl, &mut keys.ctrl_side, "Ctrl"),
+                                    (&mut keys.alt, &mut keys.alt_side, "Alt"),
+                                    (&mut keys.win, &mut keys.win_side, "Win"),
+                                ] {
+                                    ui.checkbox(held, name);
+
+                                    if *held {
+                                        egui::ComboBox::new(
+                                            format!("bindings_{:?}_{}", action, name),
+                                            "",
+                                        )
+                                        .width(60.0)
+                                        .selected_text(format!("{:?}", side))
+                                        .show_ui(
+                                            ui,
+                                            |ui| {
+                                                for option in Side::variants() {
+                                                    ui.selectable_value(
+                                                        side,
+                                                        option,
+                                                        format!("{:?}", option),
+                                                    );
+                                                }
+                                            },
+                                        );
+                                    }
+                                }
                             });
+
+                            if !clashes.is_empty() {
+                                ui.colored_label(
+                                    error_color,
+                                    format!("Also bound to {}", clashes.join(", ")),
+                                );
+                            }
+
+                            if let Some(reason) = reserved(keys) {
+                                ui.colored_label(
+                                    warn_color,
+                                    format!("{} {} in Windows", keys, reason),
+                                );
+                            }
                         });
                     });
             });
 
+        if before.is_some_and(|before| before != self.config()) {
+            self.apply_key_bindings();
+            self.save_config();
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
+            if let Some(config_error) = &self.config_error {
+                ui.colored_label(ui.visuals().error_fg_color, config_error);
+                ui.separator();
+            }
+
             ui.horizontal(|ui| {
                 ui.heading("Windows");
                 ui.label(self.windows_manager.windows.len().to_string());
@@ -175,6 +1026,18 @@ impl eframe::App for App {
                 ui.label(self.windows_manager.floating.len().to_string());
             });
 
+            if let Some(workspace) = self.workspace_manager.focused_workspace_mut() {
+                ui.horizontal(|ui| {
+                    ui.heading("Workspace");
+                    ui.label(format!(
+                        "{} ({}, {} managed)",
+                        workspace,
+                        workspace.layout_name(),
+                        workspace.managed_windows.len()
+                    ));
+                });
+            }
+
             ui.separator();
 
             egui::ScrollArea::vertical()
@@ -261,19 +1124,26 @@ impl eframe::App for App {
                 .find(|window| window.is_mouse_moving);
 
             ui.horizontal_centered(|ui| {
-                let monitor = unsafe {
-                    // TODO: Set to absolute min?
-                    let mut point = POINT::default();
-                    let _ = GetCursorPos(&mut point);
-                    self.monitor_container
-                        .get_monitor_at_point(point.x, point.y)
-                };
+                // TODO: Set to absolute min?
+                let point = self.windows_manager.backend().cursor_position();
+                let monitor = self
+                    .workspace_manager
+                    .monitor_container
+                    .get_monitor_at_point(point.x, point.y);
 
                 ui.monospace(format!(
                     "[Mouse: display({}), primary({})]",
                     &monitor.screen.device_name, &monitor.screen.primary
                 ));
 
+                if let Some((workspace, placements)) = &self.last_layout {
+                    ui.monospace(format!(
+                        "[Layout: workspace({}), windows({})]",
+                        workspace,
+                        placements.len()
+                    ));
+                }
+
                 if let Some(moving_window) = moving_window {
                     let location = moving_window.location();
                     ui.horizontal(|ui| {
@@ -292,6 +1162,246 @@ impl eframe::App for App {
     }
 
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        if !conflicts(&self.key_bindings).is_empty() {
+            warn!("Not saving, some bindings share keys");
+            return;
+        }
+
+        self.settings.workspace_layouts = self.workspace_manager.workspace_layouts();
         eframe::set_value(storage, eframe::APP_KEY, self);
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        info!("Showing every window before exiting");
+        self.workspace_manager.show_all_windows();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::simulated_desktop::{dual_monitor_desktop, toggle_tiling};
+    use crate::csharp::structs::Rectangle;
+
+    #[test]
+    fn recording_binds_the_next_chord() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let handle = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.focus(handle);
+        desktop.pump(&mut app);
+
+        let chord = Keys {
+            ctrl: true,
+            ctrl_side: Side::Left,
+            key: VirtualKey::F,
+            ..Default::default()
+        };
+
+        app.recording = Some(Action::ToggleFocusedWindowTiling);
+        desktop.press(Keys {
+            ctrl: true,
+            ctrl_side: Side::Left,
+            key: VirtualKey::LeftControl,
+            ..Default::default()
+        });
+        desktop.press(toggle_tiling());
+        desktop.pump(&mut app);
+
+        assert_eq!(app.recording, None);
+        assert_eq!(
+            app.key_bindings[&Action::ToggleFocusedWindowTiling],
+            toggle_tiling()
+        );
+        assert!(app.windows_manager.floating.is_empty());
+
+        app.recording = Some(Action::ToggleFocusedWindowTiling);
+        desktop.press(chord);
+        desktop.pump(&mut app);
+
+        assert_eq!(
+            app.key_bindings[&Action::ToggleFocusedWindowTiling].to_string(),
+            "ctrl+F"
+        );
+        assert!(!desktop.press(chord));
+        desktop.pump(&mut app);
+        assert!(app.windows_manager.floating.contains_key(&handle));
+
+        app.recording = Some(Action::ToggleFocusedWindowTiling);
+        desktop.press(Keys {
+            key: VirtualKey::Esc,
+            ..Default::default()
+        });
+        desktop.pump(&mut app);
+
+        assert_eq!(app.recording, None);
+        assert_eq!(
+            app.key_bindings[&Action::ToggleFocusedWindowTiling].to_string(),
+            "ctrl+F"
+        );
+    }
+
+    #[test]
+    fn actions_dispatch_from_key_presses() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let moved = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        let closed = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.pump(&mut app);
+
+        desktop.focus(moved);
+        desktop.press(Keys {
+            alt: true,
+            shift: true,
+            key: VirtualKey::E,
+            ..Default::default()
+        });
+        desktop.press(Keys {
+            alt: true,
+            shift: true,
+            key: VirtualKey::Equals,
+            ..Default::default()
+        });
+        desktop.pump(&mut app);
+
+        assert_eq!(app.workspace_manager.workspace_for_window(moved), Some(1));
+        assert_eq!(desktop.rect(moved), Rectangle::new(-2560, -360, 2560, 1400));
+        assert_eq!(app.settings.gaps.inner, 2);
+
+        desktop.focus(closed);
+        desktop.press(Keys {
+            alt: true,
+            shift: true,
+            key: VirtualKey::C,
+            ..Default::default()
+        });
+        desktop.pump(&mut app);
+
+        assert!(!app.windows_manager.windows.contains_key(&closed));
+    }
+
+    #[test]
+    fn requests_run_actions_and_answer_queries() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| desktop.open_window(0, "Notepad", "Notepad", "notepad.exe"))
+            .collect();
+        desktop.focus(handles[1]);
+        desktop.pump(&mut app);
+
+        let Response::FocusedWindow(Some(focused)) =
+            app.handle_request(Request::Query(Query::FocusedWindow))
+        else {
+            panic!("expected the focused window");
+        };
+        assert_eq!(focused.handle, handles[1]);
+        assert_eq!(focused.workspace.as_deref(), Some("1"));
+
+        assert_eq!(
+            app.handle_request(Request::Command(Action::ToggleFocusedWindowTiling)),
+            Response::Ok
+        );
+        desktop.pump(&mut app);
+
+        let Response::Windows(windows) = app.handle_request(Request::Query(Query::Windows)) else {
+            panic!("expected windows");
+        };
+        assert_eq!(windows.len(), 2);
+        assert!(windows
+            .iter()
+            .all(|w| w.floating == (w.handle == handles[1])));
+
+        assert_eq!(
+            app.handle_request(Request::Command(Action::SwitchToWorkspace(9))),
+            Response::Error("No workspace at index 9".to_string())
+        );
+        assert_eq!(
+            app.handle_request(Request::Command(Action::MoveFocusedWindowToMonitor(2))),
+            Response::Error("No monitor at index 2".to_string())
+        );
+
+        let Response::Workspaces(workspaces) =
+            app.handle_request(Request::Query(Query::Workspaces))
+        else {
+            panic!("expected workspaces");
+        };
+        assert_eq!(workspaces.len(), 9);
+        assert_eq!(workspaces[0].windows, handles);
+        assert_eq!(workspaces[1].monitor, Some(1));
+        assert_eq!(workspaces[2].monitor, None);
+
+        let Response::Monitors(monitors) = app.handle_request(Request::Query(Query::Monitors))
+        else {
+            panic!("expected monitors");
+        };
+        assert_eq!(monitors.len(), 2);
+        assert_eq!(
+            monitors[1].working_area,
+            Rectangle::new(-2560, -360, 2560, 1400)
+        );
+        assert_eq!(monitors[1].workspace.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn defaults_on_taken_keys_are_left_unbound() {
+        let path =
+            std::env::temp_dir().join(format!("tile-rs-{}-unbound.toml", std::process::id()));
+        let key_bindings = HashMap::from([(Action::ResetLayout, toggle_tiling())]);
+        Config::new(&Settings::default(), &key_bindings, &HashSet::new())
+            .save(&path)
+            .unwrap();
+
+        let desktop = dual_monitor_desktop();
+        let app = App::with_backend(desktop.backend()).with_config_path(Some(path.clone()));
+
+        assert_eq!(app.config_error, None);
+        assert_eq!(app.unbound, [Action::ToggleFocusedWindowTiling]);
+        assert!(!app
+            .key_bindings
+            .contains_key(&Action::ToggleFocusedWindowTiling));
+        assert_eq!(app.key_bindings[&Action::ResetLayout], toggle_tiling());
+        assert!(conflicts(&app.key_bindings).is_empty());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    // the client side is a Unix socket, a named pipe needs Win32 to connect
+    #[cfg(not(windows))]
+    #[test]
+    fn failed_reloads_answer_with_the_error() {
+        let desktop = dual_monitor_desktop();
+        let id = std::process::id();
+        let config_path = std::env::temp_dir().join(format!("tile-rs-reload-{}.toml", id));
+        let ipc_path = std::env::temp_dir().join(format!("tile-rs-reload-{}.sock", id));
+        let _ = std::fs::remove_file(&config_path);
+
+        let mut app = desktop
+            .app()
+            .with_config_path(Some(config_path.clone()))
+            .with_ipc_path(ipc_path.clone());
+        std::fs::write(&config_path, "[settings.gaps]\ninner = -\n").unwrap();
+
+        let client = std::thread::spawn(move || {
+            let mut client = crate::ipc::client::Client::connect(&ipc_path).unwrap();
+            client
+                .request::<_, Response>(&Request::Command(Action::ReloadConfig))
+                .unwrap()
+        });
+
+        while !client.is_finished() {
+            app.handle_ipc();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        let Response::Error(message) = client.join().unwrap() else {
+            panic!("expected an error");
+        };
+        assert_eq!(Some(message), app.config_error);
+
+        std::fs::remove_file(config_path).unwrap();
+    }
 }
@@ -0,0 +1,326 @@
+use crate::backends::{ShowCommand, WindowBackend, WindowEvent};
+use crate::classes::keys::Keys;
+use crate::csharp::screen::Screen;
+use crate::csharp::structs::{Point, Rectangle};
+use anyhow::{bail, Result};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+
+const FIRST_HANDLE: isize = 0x1000;
+const FIRST_MONITOR: isize = 0x100;
+
+#[derive(Debug, Clone, Default)]
+pub struct FakeWindow {
+    pub title: String,
+    pub class: String,
+    pub process_id: u32,
+    pub process_name: String,
+    pub rect: Rectangle,
+    pub visible: bool,
+    pub cloaked: bool,
+    pub minimized: bool,
+    pub maximized: bool,
+    pub tool_window: bool,
+}
+
+#[allow(dead_code)]
+impl FakeWindow {
+    pub fn new(title: &str, class: &str, process_name: &str, rect: Rectangle) -> Self {
+        Self {
+            title: title.to_string(),
+            class: class.to_string(),
+            process_id: 1,
+            process_name: process_name.to_string(),
+            rect,
+            visible: true,
+            ..Default::default()
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Default)]
+struct FakeDesktop {
+    windows: BTreeMap<isize, FakeWindow>,
+    screens: Vec<Screen>,
+    foreground: isize,
+    cursor: Point,
+    next_handle: isize,
+    window_events: VecDeque<(WindowEvent, isize)>,
+    keys: VecDeque<Keys>,
+    swallowed_keys: Vec<Keys>,
+    mouse: VecDeque<()>,
+}
+
+#[derive(Debug)]
+pub struct FakeBackend {
+    desktop: Mutex<FakeDesktop>,
+}
+
+impl Default for FakeBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
+impl FakeBackend {
+    pub fn new() -> Self {
+        let backend = Self {
+            desktop: Mutex::new(FakeDesktop {
+                next_handle: FIRST_HANDLE,
+                ..Default::default()
+            }),
+        };
+
+        backend.add_screen(
+            Rectangle::new(0, 0, 1920, 1080),
+            Rectangle::new(0, 0, 1920, 1040),
+        );
+
+        backend
+    }
+
+    fn desktop(&self) -> MutexGuard<'_, FakeDesktop> {
+        self.desktop.lock().unwrap()
+    }
+
+    pub fn clear_screens(&self) {
+        self.desktop().screens.clear();
+    }
+
+    pub fn add_screen(&self, bounds: Rectangle, working_area: Rectangle) -> isize {
+        let mut desktop = self.desktop();
+        let index = desktop.screens.len();
+        let hmonitor = FIRST_MONITOR + index as isize;
+
+        desktop.screens.push(Screen {
+            bounds,
+            primary: index == 0,
+            device_name: format!("\\\\.\\DISPLAY{}", index + 1),
+            hmonitor,
+            bit_depth: 32,
+            working_area,
+        });
+
+        hmonitor
+    }
+
+    pub fn add_window(&self, window: FakeWindow) -> isize {
+        let mut desktop = self.desktop();
+        let handle = desktop.next_handle;
+        desktop.next_handle += 1;
+        desktop.windows.insert(handle, window);
+        handle
+    }
+
+    pub fn remove_window(&self, handle: isize) -> Option<FakeWindow> {
+        self.desktop().windows.remove(&handle)
+    }
+
+    pub fn window(&self, handle: isize) -> Option<FakeWindow> {
+        self.desktop().windows.get(&handle).cloned()
+    }
+
+    pub fn update_window<F: FnOnce(&mut FakeWindow)>(&self, handle: isize, update: F) {
+        if let Some(window) = self.desktop().windows.get_mut(&handle) {
+            update(window);
+        }
+    }
+
+    pub fn set_foreground_window(&self, handle: isize) {
+        self.desktop().foreground = handle;
+    }
+
+    pub fn set_cursor_position(&self, point: Point) {
+        self.desktop().cursor = point;
+    }
+
+    pub fn push_window_event(&self, event: WindowEvent, handle: isize) {
+        self.desktop().window_events.push_back((event, handle));
+    }
+
+    pub fn pending_window_events(&self) -> usize {
+        self.desktop().window_events.len()
+    }
+
+    pub fn pending_keys(&self) -> usize {
+        self.desktop().keys.len()
+    }
+
+    pub fn push_keys(&self, keys: Keys) {
+        self.desktop().keys.push_back(keys);
+    }
+
+    // whether the hook would keep `keys` from the focused window
+    pub fn swallows(&self, keys: &Keys) -> bool {
+        self.desktop()
+            .swallowed_keys
+            .iter()
+            .any(|swallowed| swallowed.matches(keys))
+    }
+
+    pub fn push_mouse(&self) {
+        self.desktop().mouse.push_back(());
+    }
+
+    fn with_window<T: Default, F: FnOnce(&FakeWindow) -> T>(&self, handle: isize, f: F) -> T {
+        self.desktop()
+            .windows
+            .get(&handle)
+            .map(f)
+            .unwrap_or_default()
+    }
+
+    fn nearest_screen(&self, point: Point) -> Screen {
+        let desktop = self.desktop();
+
+        desktop
+            .screens
+            .iter()
+            .find(|s| s.bounds.contains_point(point))
+            .or_else(|| {
+                desktop.screens.iter().min_by_key(|s| {
+                    let dx = (s.bounds.x + s.bounds.width / 2 - point.x) as i64;
+                    let dy = (s.bounds.y + s.bounds.height / 2 - point.y) as i64;
+                    dx * dx + dy * dy
+                })
+            })
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl WindowBackend for FakeBackend {
+    fn init(&self) {}
+
+    fn poll_window_event(&self) -> Option<(WindowEvent, isize)> {
+        self.desktop().window_events.pop_front()
+    }
+
+    fn poll_keys(&self) -> Option<Keys> {
+        self.desktop().keys.pop_front()
+    }
+
+    fn set_swallowed_keys(&self, keys: Vec<Keys>) {
+        self.desktop().swallowed_keys = keys;
+    }
+
+    fn poll_mouse(&self) -> Option<()> {
+        self.desktop().mouse.pop_front()
+    }
+
+    fn enumerate_windows(&self) -> Vec<isize> {
+        self.desktop().windows.keys().copied().collect()
+    }
+
+    fn is_app_window(&self, handle: isize) -> bool {
+        self.with_window(handle, |w| w.visible)
+    }
+
+    fn is_alt_tab_window(&self, handle: isize) -> bool {
+        self.with_window(handle, |w| !w.tool_window)
+    }
+
+    fn is_cloaked(&self, handle: isize) -> bool {
+        self.with_window(handle, |w| w.cloaked)
+    }
+
+    fn title(&self, handle: isize) -> String {
+        self.with_window(handle, |w| w.title.clone())
+    }
+
+    fn class(&self, handle: isize) -> String {
+        self.with_window(handle, |w| w.class.clone())
+    }
+
+    fn process(&self, handle: isize) -> Result<(u32, String)> {
+        match self.desktop().windows.get(&handle) {
+            Some(window) => Ok((window.process_id, window.process_name.clone())),
+            None => bail!("Failed to get process id"),
+        }
+    }
+
+    fn rect(&self, handle: isize) -> Rectangle {
+        self.with_window(handle, |w| w.rect)
+    }
+
+    fn frame_offset(&self, _handle: isize) -> Rectangle {
+        Rectangle::default()
+    }
+
+    fn is_minimized(&self, handle: isize) -> bool {
+        self.with_window(handle, |w| w.minimized)
+    }
+
+    fn is_maximized(&self, handle: isize) -> bool {
+        self.with_window(handle, |w| w.maximized)
+    }
+
+    fn is_fullscreen(&self, handle: isize) -> bool {
+        let rect = self.rect(handle);
+        self.screen_from_rectangle(rect).bounds == rect
+    }
+
+    fn foreground_window(&self) -> isize {
+        self.desktop().foreground
+    }
+
+    fn show(&self, handle: isize, command: ShowCommand) {
+        self.update_window(handle, |w| match command {
+            ShowCommand::Hide => w.visible = false,
+            ShowCommand::Normal => {
+                w.visible = true;
+                w.minimized = false;
+                w.maximized = false;
+            }
+            ShowCommand::Minimize | ShowCommand::ShowMinimized => {
+                w.visible = true;
+                w.minimized = true;
+                w.maximized = false;
+            }
+            ShowCommand::ShowMaximized => {
+                w.visible = true;
+                w.minimized = false;
+                w.maximized = true;
+            }
+        });
+    }
+
+    fn set_window_positions(&self, positions: &[(isize, Rectangle)]) {
+        for (handle, rect) in positions {
+            self.update_window(*handle, |w| w.rect = *rect);
+        }
+    }
+
+    fn focus(&self, handle: isize) {
+        self.set_foreground_window(handle);
+    }
+
+    fn bring_to_top(&self, _handle: isize) {}
+
+    fn close(&self, handle: isize) {
+        if self.remove_window(handle).is_some() {
+            self.push_window_event(WindowEvent::Destroy, handle);
+        }
+    }
+
+    fn screens(&self) -> Vec<Screen> {
+        self.desktop().screens.clone()
+    }
+
+    fn screen_from_point(&self, point: Point) -> Screen {
+        self.nearest_screen(point)
+    }
+
+    fn screen_from_rectangle(&self, rect: Rectangle) -> Screen {
+        self.nearest_screen(Point {
+            x: rect.x + rect.width / 2,
+            y: rect.y + rect.height / 2,
+        })
+    }
+
+    fn cursor_position(&self) -> Point {
+        self.desktop().cursor
+    }
+}
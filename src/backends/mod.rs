@@ -0,0 +1,130 @@
+use crate::classes::keys::Keys;
+use crate::csharp::screen::Screen;
+use crate::csharp::structs::{Point, Rectangle};
+use anyhow::Result;
+use lazy_static::lazy_static;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+pub mod fake_backend;
+#[cfg(test)]
+pub mod simulated_desktop;
+#[cfg(windows)]
+pub mod win32_backend;
+
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum WindowEvent {
+    Show,
+    Destroy,
+    Cloaked,
+    Uncloaked,
+    MinimizeStart,
+    MinimizeEnd,
+    Foreground,
+    MoveSizeStart,
+    MoveSizeEnd,
+    LocationChange,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ShowCommand {
+    Hide,
+    Normal,
+    Minimize,
+    ShowMinimized,
+    ShowMaximized,
+}
+
+pub trait WindowBackend: Send + Sync {
+    // install the platform hooks that feed the event queues
+    fn init(&self);
+
+    // take the next pending window event
+    fn poll_window_event(&self) -> Option<(WindowEvent, isize)>;
+
+    // take the next pending key chord
+    fn poll_keys(&self) -> Option<Keys>;
+
+    // chords the keyboard hook keeps from the focused window, checked as they are pressed
+    fn set_swallowed_keys(&self, keys: Vec<Keys>);
+
+    // take the next pending mouse button release
+    fn poll_mouse(&self) -> Option<()>;
+
+    // handles of every top level window
+    fn enumerate_windows(&self) -> Vec<isize>;
+
+    // visible, activatable, non-child window
+    fn is_app_window(&self, handle: isize) -> bool;
+
+    // window that would be listed by alt-tab
+    fn is_alt_tab_window(&self, handle: isize) -> bool;
+
+    // window hidden by the compositor (e.g. on another virtual desktop)
+    fn is_cloaked(&self, handle: isize) -> bool;
+
+    fn title(&self, handle: isize) -> String;
+
+    fn class(&self, handle: isize) -> String;
+
+    // process id and full executable path of the owning process
+    fn process(&self, handle: isize) -> Result<(u32, String)>;
+
+    // outer window rectangle
+    fn rect(&self, handle: isize) -> Rectangle;
+
+    // difference between the outer rectangle and the visible frame
+    fn frame_offset(&self, handle: isize) -> Rectangle;
+
+    fn is_minimized(&self, handle: isize) -> bool;
+
+    fn is_maximized(&self, handle: isize) -> bool;
+
+    fn is_fullscreen(&self, handle: isize) -> bool;
+
+    fn foreground_window(&self) -> isize;
+
+    fn show(&self, handle: isize, command: ShowCommand);
+
+    // move and resize a batch of windows in one go
+    fn set_window_positions(&self, positions: &[(isize, Rectangle)]);
+
+    fn focus(&self, handle: isize);
+
+    fn bring_to_top(&self, handle: isize);
+
+    fn close(&self, handle: isize);
+
+    fn screens(&self) -> Vec<Screen>;
+
+    fn screen_from_point(&self, point: Point) -> Screen;
+
+    fn screen_from_rectangle(&self, rect: Rectangle) -> Screen;
+
+    fn cursor_position(&self) -> Point;
+}
+
+impl Debug for dyn WindowBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WindowBackend").finish()
+    }
+}
+
+lazy_static! {
+    static ref DEFAULT_BACKEND: Arc<dyn WindowBackend> = create_default_backend();
+}
+
+#[cfg(windows)]
+fn create_default_backend() -> Arc<dyn WindowBackend> {
+    Arc::new(win32_backend::Win32Backend::new())
+}
+
+#[cfg(not(windows))]
+fn create_default_backend() -> Arc<dyn WindowBackend> {
+    Arc::new(fake_backend::FakeBackend::new())
+}
+
+pub fn default_backend() -> Arc<dyn WindowBackend> {
+    DEFAULT_BACKEND.clone()
+}
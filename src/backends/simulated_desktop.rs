@@ -0,0 +1,268 @@
+use crate::app::App;
+use crate::backends::fake_backend::{FakeBackend, FakeWindow};
+use crate::backends::{WindowBackend, WindowEvent};
+use crate::classes::keys::{Keys, VirtualKey};
+use crate::classes::workspace_layout::WorkspaceLayout;
+use crate::context::context;
+use crate::csharp::structs::Rectangle;
+use crate::layout_engines::LayoutEngineType;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const WINDOW_WIDTH: i32 = 800;
+const WINDOW_HEIGHT: i32 = 600;
+const CASCADE_OFFSET: i32 = 32;
+
+/// Scriptable desktop for driving the `WindowsManager` event pipeline without Win32.
+pub struct SimulatedDesktop {
+    backend: Arc<FakeBackend>,
+    monitors: Vec<Rectangle>,
+    opened: HashMap<usize, i32>,
+}
+
+impl Default for SimulatedDesktop {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulatedDesktop {
+    pub fn new() -> Self {
+        let backend = FakeBackend::new();
+        backend.clear_screens();
+
+        Self {
+            backend: Arc::new(backend),
+            monitors: Vec::new(),
+            opened: HashMap::new(),
+        }
+    }
+
+    pub fn backend(&self) -> Arc<dyn WindowBackend> {
+        self.backend.clone()
+    }
+
+    // Monitors need to be added first, the app only sees the monitors present when it is created.
+    pub fn app(&self) -> App {
+        let mut app = App::with_backend(self.backend());
+        context(&mut app);
+        app
+    }
+
+    /// Adds a monitor and returns its index, the first one added is the primary monitor.
+    pub fn add_monitor(&mut self, bounds: Rectangle, working_area: Rectangle) -> usize {
+        self.backend.add_screen(bounds, working_area);
+        self.monitors.push(working_area);
+        self.monitors.len() - 1
+    }
+
+    /// Opens a window cascaded inside the working area of `monitor` and queues its show event.
+    pub fn open_window(
+        &mut self,
+        monitor: usize,
+        class: &str,
+        title: &str,
+        process: &str,
+    ) -> isize {
+        let working_area = self.monitors[monitor];
+        let cascade = self.opened.entry(monitor).or_insert(0);
+
+        let rect = Rectangle::new(
+            working_area.x + *cascade * CASCADE_OFFSET,
+            working_area.y + *cascade * CASCADE_OFFSET,
+            WINDOW_WIDTH.min(working_area.width),
+            WINDOW_HEIGHT.min(working_area.height),
+        );
+
+        *cascade += 1;
+
+        let handle = self
+            .backend
+            .add_window(FakeWindow::new(title, class, process, rect));
+
+        self.backend.push_window_event(WindowEvent::Show, handle);
+        handle
+    }
+
+    pub fn close_window(&self, handle: isize) {
+        self.backend.close(handle);
+    }
+
+    pub fn cloak(&self, handle: isize) {
+        self.backend.update_window(handle, |w| w.cloaked = true);
+        self.backend.push_window_event(WindowEvent::Cloaked, handle);
+    }
+
+    pub fn uncloak(&self, handle: isize) {
+        self.backend.update_window(handle, |w| w.cloaked = false);
+        self.backend
+            .push_window_event(WindowEvent::Uncloaked, handle);
+    }
+
+    pub fn minimize(&self, handle: isize) {
+        self.backend.update_window(handle, |w| w.minimized = true);
+        self.backend
+            .push_window_event(WindowEvent::MinimizeStart, handle);
+    }
+
+    pub fn restore(&self, handle: isize) {
+        self.backend.update_window(handle, |w| w.minimized = false);
+        self.backend
+            .push_window_event(WindowEvent::MinimizeEnd, handle);
+    }
+
+    pub fn focus(&self, handle: isize) {
+        self.backend.set_foreground_window(handle);
+        self.backend
+            .push_window_event(WindowEvent::Foreground, handle);
+    }
+
+    pub fn start_move(&self, handle: isize) {
+        self.backend
+            .push_window_event(WindowEvent::MoveSizeStart, handle);
+    }
+
+    pub fn move_window(&self, handle: isize, rect: Rectangle) {
+        self.backend.update_window(handle, |w| w.rect = rect);
+        self.backend
+            .push_window_event(WindowEvent::LocationChange, handle);
+    }
+
+    pub fn end_move(&self, handle: isize) {
+        self.backend
+            .push_window_event(WindowEvent::MoveSizeEnd, handle);
+    }
+
+    // Returns whether the keys also reach the focused window.
+    pub fn press(&self, keys: Keys) -> bool {
+        self.backend.push_keys(keys);
+        !self.backend.swallows(&keys)
+    }
+
+    pub fn rect(&self, handle: isize) -> Rectangle {
+        self.backend.rect(handle)
+    }
+
+    pub fn is_minimized(&self, handle: isize) -> bool {
+        self.backend.is_minimized(handle)
+    }
+
+    pub fn foreground_window(&self) -> isize {
+        self.backend.foreground_window()
+    }
+
+    pub fn is_visible(&self, handle: isize) -> bool {
+        self.backend.window(handle).is_some_and(|w| w.visible)
+    }
+
+    /// Feeds every queued event and key press through the app, and the events the app
+    /// raised itself, like a frame of `App::update`.
+    pub fn pump(&self, app: &mut App) {
+        loop {
+            while self.backend.pending_window_events() > 0 {
+                app.windows_manager.handle_window();
+            }
+
+            app.workspace_manager.handle_events();
+
+            while self.backend.pending_keys() > 0 {
+                app.handle_keys();
+            }
+
+            app.workspace_manager.handle_events();
+
+            if self.backend.pending_window_events() == 0 && self.backend.pending_keys() == 0 {
+                break;
+            }
+        }
+
+        app.broadcast_events();
+    }
+}
+
+// The desktop the end-to-end tests of every module share: a 1080p primary monitor and a
+// 1440p one up and to the left of it.
+pub fn dual_monitor_desktop() -> SimulatedDesktop {
+    let mut desktop = SimulatedDesktop::new();
+    desktop.add_monitor(
+        Rectangle::new(0, 0, 1920, 1080),
+        Rectangle::new(0, 0, 1920, 1040),
+    );
+    desktop.add_monitor(
+        Rectangle::new(-2560, -360, 2560, 1440),
+        Rectangle::new(-2560, -360, 2560, 1400),
+    );
+    desktop
+}
+
+// lays out every workspace with `engine` through the settings
+pub fn set_layout(app: &mut App, engine: LayoutEngineType) {
+    for name in app.settings.workspace_names.clone() {
+        let layout = WorkspaceLayout {
+            layout_engine_type: engine,
+            ..Default::default()
+        };
+        app.settings.workspace_layouts.insert(name, layout);
+    }
+    app.apply_settings();
+}
+
+// the default binding of `ToggleFocusedWindowTiling`
+pub fn toggle_tiling() -> Keys {
+    Keys {
+        alt: true,
+        key: VirtualKey::T,
+        ..Default::default()
+    }
+}
+
+pub fn alt(key: VirtualKey) -> Keys {
+    Keys {
+        alt: true,
+        key,
+        ..Default::default()
+    }
+}
+
+pub fn alt_shift(key: VirtualKey) -> Keys {
+    Keys {
+        alt: true,
+        shift: true,
+        key,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_windows_on_secondary_monitor() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let handles: Vec<_> = (0..3)
+            .map(|i| {
+                let title = format!("Untitled {} - Notepad", i);
+                desktop.open_window(1, "Notepad", &title, "notepad.exe")
+            })
+            .collect();
+
+        desktop.pump(&mut app);
+
+        assert_eq!(app.windows_manager.windows.len(), 3);
+        assert_eq!(
+            desktop.rect(handles[0]),
+            Rectangle::new(-2560, -360, 800, 600)
+        );
+        assert_eq!(
+            desktop.rect(handles[2]),
+            Rectangle::new(-2496, -296, 800, 600)
+        );
+        assert_eq!(
+            app.windows_manager.windows[&handles[1]].process_file_name(),
+            "notepad.exe"
+        );
+    }
+}
@@ -0,0 +1,524 @@
+use crate::backends::{ShowCommand, WindowBackend, WindowEvent};
+use crate::classes::keys::Keys;
+use crate::csharp::screen::Screen;
+use crate::csharp::structs::{Point, Rectangle};
+use crate::helpers::win32_helpers;
+use anyhow::{bail, Result};
+use crossbeam_channel::{Receiver, Sender};
+use lazy_static::lazy_static;
+use log::{error, info, trace, warn};
+use std::ffi::c_void;
+use std::mem::size_of;
+use std::sync::RwLock;
+use windows::Win32::Foundation::{
+    BOOL, HMODULE, HWND, LPARAM, LRESULT, MAX_PATH, POINT, RECT, TRUE, WPARAM,
+};
+use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_EXTENDED_FRAME_BOUNDS};
+use windows::Win32::Graphics::Gdi::{
+    GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::System::ProcessStatus::K32GetModuleFileNameExW;
+use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ};
+use windows::Win32::UI::Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK};
+use windows::Win32::UI::WindowsAndMessaging::{
+    BeginDeferWindowPos, BringWindowToTop, CallNextHookEx, DeferWindowPos, DispatchMessageW,
+    EndDeferWindowPos, EnumWindows, GetClassNameW, GetCursorPos, GetForegroundWindow, GetMessageW,
+    GetWindowRect, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId, IsIconic,
+    IsZoomed, SendNotifyMessageW, SetForegroundWindow, SetWindowsHookExW, ShowWindow,
+    TranslateMessage, UnhookWindowsHookEx, EVENT_OBJECT_CLOAKED, EVENT_OBJECT_DESTROY,
+    EVENT_OBJECT_LOCATIONCHANGE, EVENT_OBJECT_SHOW, EVENT_OBJECT_UNCLOAKED,
+    EVENT_SYSTEM_FOREGROUND, EVENT_SYSTEM_MINIMIZEEND, EVENT_SYSTEM_MINIMIZESTART,
+    EVENT_SYSTEM_MOVESIZEEND, EVENT_SYSTEM_MOVESIZESTART, MSG, SC_CLOSE, SWP_FRAMECHANGED,
+    SWP_NOACTIVATE, SWP_NOCOPYBITS, SWP_NOOWNERZORDER, SWP_NOZORDER, SW_HIDE, SW_MINIMIZE,
+    SW_SHOWMAXIMIZED, SW_SHOWMINIMIZED, SW_SHOWNOACTIVATE, WH_KEYBOARD_LL, WH_MOUSE_LL,
+    WINEVENT_OUTOFCONTEXT, WM_LBUTTONUP, WM_SYSCOMMAND,
+};
+
+type EventType = (u32, isize);
+
+lazy_static! {
+    static ref EVENT: (Sender<EventType>, Receiver<EventType>) = crossbeam_channel::unbounded();
+}
+
+lazy_static! {
+    static ref KEYS: (Sender<Keys>, Receiver<Keys>) = crossbeam_channel::unbounded();
+}
+
+lazy_static! {
+    static ref SWALLOWED_KEYS: RwLock<Vec<Keys>> = RwLock::new(Vec::new());
+}
+
+lazy_static! {
+    static ref MOUSE: (Sender<()>, Receiver<()>) = crossbeam_channel::unbounded();
+}
+
+#[derive(Debug, Default)]
+pub struct Win32Backend;
+
+impl Win32Backend {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn register_window_hook(event_min: u32, event_max: u32, hmodule: HMODULE) -> HWINEVENTHOOK {
+        unsafe {
+            SetWinEventHook(
+                event_min,
+                event_max,
+                hmodule,
+                Some(Self::window_callback),
+                0,
+                0,
+                WINEVENT_OUTOFCONTEXT,
+            )
+        }
+    }
+
+    unsafe extern "system" fn enum_windows_callback(hwnd: HWND, userdata: LPARAM) -> BOOL {
+        let windows = &mut *(userdata.0 as *mut Vec<HWND>);
+        windows.push(hwnd);
+
+        TRUE
+    }
+
+    unsafe extern "system" fn mouse_callback(
+        n_code: i32,
+        w_param: WPARAM,
+        l_param: LPARAM,
+    ) -> LRESULT {
+        if w_param.0 == WM_LBUTTONUP as usize && MOUSE.0.send(()).is_err() {
+            error!("mouse_callback | failed to send");
+        }
+
+        CallNextHookEx(None, n_code, w_param, l_param)
+    }
+
+    unsafe extern "system" fn keyboard_callback(
+        n_code: i32,
+        w_param: WPARAM,
+        l_param: LPARAM,
+    ) -> LRESULT {
+        if let Some(keys) = Keys::new(n_code, w_param, l_param) {
+            if KEYS.0.send(keys).is_err() {
+                error!("keyboard_callback | failed to send");
+            };
+
+            let swallow = SWALLOWED_KEYS
+                .read()
+                .is_ok_and(|swallowed| swallowed.iter().any(|k| k.matches(&keys)));
+
+            if swallow {
+                trace!("keyboard_callback | swallowed: {}", keys);
+                return LRESULT(1);
+            }
+        };
+
+        CallNextHookEx(None, n_code, w_param, l_param)
+    }
+
+    unsafe extern "system" fn window_callback(
+        _h_win_event_hook: HWINEVENTHOOK,
+        event_type: u32,
+        hwnd: HWND,
+        id_object: i32,
+        id_child: i32,
+        _id_event_thread: u32,
+        _dwms_event_time: u32,
+    ) {
+        let hwnd = hwnd.0;
+
+        if !(id_child == 0 && id_object == 0 && hwnd != 0) {
+            return;
+        }
+
+        if EVENT.0.send((event_type, hwnd)).is_err() {
+            error!(
+                "event_callback | failed to send | event_type: {:?}, hwnd: 0x{:X}",
+                event_type, hwnd
+            );
+        }
+    }
+}
+
+impl WindowBackend for Win32Backend {
+    fn init(&self) {
+        info!("Initializing hooks");
+
+        let module_handle = unsafe {
+            GetModuleHandleW(None).unwrap_or_else(|e| {
+                error!("Failed GetModuleHandleW: {:?}", e);
+                std::process::exit(69);
+            })
+        };
+
+        let event_windows = [
+            Self::register_window_hook(EVENT_OBJECT_DESTROY, EVENT_OBJECT_SHOW, module_handle),
+            Self::register_window_hook(EVENT_OBJECT_CLOAKED, EVENT_OBJECT_UNCLOAKED, module_handle),
+            Self::register_window_hook(
+                EVENT_SYSTEM_MINIMIZESTART,
+                EVENT_SYSTEM_MINIMIZEEND,
+                module_handle,
+            ),
+            Self::register_window_hook(
+                EVENT_SYSTEM_MOVESIZESTART,
+                EVENT_SYSTEM_MOVESIZEEND,
+                module_handle,
+            ),
+            Self::register_window_hook(
+                EVENT_SYSTEM_FOREGROUND,
+                EVENT_SYSTEM_FOREGROUND,
+                module_handle,
+            ),
+            Self::register_window_hook(
+                EVENT_OBJECT_LOCATIONCHANGE,
+                EVENT_OBJECT_LOCATIONCHANGE,
+                module_handle,
+            ),
+        ];
+
+        let event_mouse = unsafe {
+            SetWindowsHookExW(WH_MOUSE_LL, Some(Self::mouse_callback), module_handle, 0)
+                .unwrap_or_else(|e| {
+                    error!("Failed SetWindowsHookExW[Mouse]: {:?}", e);
+                    std::process::exit(69);
+                })
+        };
+
+        let event_keyboard = unsafe {
+            SetWindowsHookExW(
+                WH_KEYBOARD_LL,
+                Some(Self::keyboard_callback),
+                module_handle,
+                0,
+            )
+            .unwrap_or_else(|e| {
+                error!("Failed SetWindowsHookExW[Keyboard]: {:?}", e);
+                std::process::exit(69);
+            })
+        };
+
+        info!("Initialized hooks");
+
+        let mut message = MSG::default();
+
+        // Handle close
+        std::thread::spawn(move || {
+            loop {
+                unsafe {
+                    if GetMessageW(&mut message, HWND(0), 0, 0).as_bool() {
+                        TranslateMessage(&message);
+                        DispatchMessageW(&message);
+                    } else {
+                        break;
+                    }
+                }
+            }
+
+            unsafe {
+                UnhookWindowsHookEx(event_mouse).unwrap_or_else(|e| {
+                    error!("Failed UnhookWindowsHookEx (mouse): {:?}", e);
+                })
+            };
+
+            unsafe {
+                UnhookWindowsHookEx(event_keyboard).unwrap_or_else(|e| {
+                    error!("Failed UnhookWindowsHookEx (keyboard): {:?}", e);
+                })
+            };
+
+            for hooks in event_windows.into_iter() {
+                unsafe {
+                    if !UnhookWinEvent(hooks).as_bool() {
+                        error!("Failed UnhookWinEvent");
+                    }
+                }
+            }
+        });
+    }
+
+    fn poll_window_event(&self) -> Option<(WindowEvent, isize)> {
+        while let Ok((event, hwnd)) = EVENT.1.try_recv() {
+            let event = match event {
+                EVENT_OBJECT_SHOW => WindowEvent::Show,
+                EVENT_OBJECT_DESTROY => WindowEvent::Destroy,
+                EVENT_OBJECT_CLOAKED => WindowEvent::Cloaked,
+                EVENT_OBJECT_UNCLOAKED => WindowEvent::Uncloaked,
+                EVENT_SYSTEM_MINIMIZESTART => WindowEvent::MinimizeStart,
+                EVENT_SYSTEM_MINIMIZEEND => WindowEvent::MinimizeEnd,
+                EVENT_SYSTEM_FOREGROUND => WindowEvent::Foreground,
+                EVENT_SYSTEM_MOVESIZESTART => WindowEvent::MoveSizeStart,
+                EVENT_SYSTEM_MOVESIZEEND => WindowEvent::MoveSizeEnd,
+                EVENT_OBJECT_LOCATIONCHANGE => WindowEvent::LocationChange,
+                _ => {
+                    error!("poll_window_event | event_type: UNKNOWN({:?})", event);
+                    continue;
+                }
+            };
+
+            return Some((event, hwnd));
+        }
+
+        None
+    }
+
+    fn poll_keys(&self) -> Option<Keys> {
+        KEYS.1.try_recv().ok()
+    }
+
+    fn set_swallowed_keys(&self, keys: Vec<Keys>) {
+        match SWALLOWED_KEYS.write() {
+            Ok(mut swallowed) => *swallowed = keys,
+            Err(e) => error!("set_swallowed_keys | {}", e),
+        }
+    }
+
+    fn poll_mouse(&self) -> Option<()> {
+        MOUSE.1.try_recv().ok()
+    }
+
+    fn enumerate_windows(&self) -> Vec<isize> {
+        let mut windows: Vec<HWND> = vec![];
+
+        unsafe {
+            let _ = EnumWindows(
+                Some(Self::enum_windows_callback),
+                LPARAM(&mut windows as *mut Vec<HWND> as isize),
+            );
+        };
+
+        windows.into_iter().map(|hwnd| hwnd.0).collect()
+    }
+
+    fn is_app_window(&self, handle: isize) -> bool {
+        win32_helpers::is_app_window(HWND(handle))
+    }
+
+    fn is_alt_tab_window(&self, handle: isize) -> bool {
+        win32_helpers::is_alt_tab_window(HWND(handle))
+    }
+
+    fn is_cloaked(&self, handle: isize) -> bool {
+        win32_helpers::is_cloaked(HWND(handle))
+    }
+
+    fn title(&self, handle: isize) -> String {
+        let hwnd = HWND(handle);
+        let length = unsafe { GetWindowTextLengthW(hwnd) };
+        let mut bytes: Vec<u16> = vec![0; length as usize + 1];
+        let _ = unsafe { GetWindowTextW(hwnd, bytes.as_mut_slice()) };
+
+        String::from_utf16_lossy(&bytes[..length as usize])
+    }
+
+    fn class(&self, handle: isize) -> String {
+        let mut class: Vec<u16> = vec![0; MAX_PATH as usize];
+
+        unsafe {
+            GetClassNameW(HWND(handle), class.as_mut_slice());
+        }
+
+        let null_pos = class.iter().position(|&c| c == 0).unwrap_or(class.len());
+        String::from_utf16_lossy(&class[..null_pos])
+    }
+
+    fn process(&self, handle: isize) -> Result<(u32, String)> {
+        let mut process_id = 0;
+        unsafe { GetWindowThreadProcessId(HWND(handle), Some(&mut process_id)) };
+
+        if process_id == 0 {
+            error!("Failed to get process id");
+            bail!("Failed to get process id");
+        }
+
+        let process_handle = match unsafe {
+            OpenProcess(
+                PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
+                false,
+                process_id,
+            )
+        } {
+            Ok(handle) => handle,
+            Err(e) => {
+                let msg = format!(
+                    "Failed to open process [hwnd: 0x{:X} | pid: {}]: {:?}",
+                    handle,
+                    process_id,
+                    e.message(),
+                );
+                warn!("{}", msg);
+                bail!("{}", msg);
+            }
+        };
+
+        trace!("process_handle: {:?}", process_handle);
+
+        let mut exe_path_bytes: Vec<u16> = vec![0; MAX_PATH as usize];
+        let exe_path_length =
+            unsafe { K32GetModuleFileNameExW(process_handle, None, &mut exe_path_bytes) };
+        let process_name = String::from_utf16_lossy(&exe_path_bytes[..exe_path_length as usize]);
+
+        Ok((process_id, process_name))
+    }
+
+    fn rect(&self, handle: isize) -> Rectangle {
+        let mut rect: RECT = RECT::default();
+
+        unsafe {
+            let _ = GetWindowRect(HWND(handle), &mut rect); // TODO: Look into this
+        }
+
+        Rectangle::from(rect)
+    }
+
+    fn frame_offset(&self, handle: isize) -> Rectangle {
+        let rect1 = self.rect(handle);
+
+        let mut rect2 = RECT::default();
+        let size = size_of::<RECT>() as u32;
+
+        unsafe {
+            let rect_ptr = &mut rect2 as *mut _ as *mut c_void;
+            let _ =
+                DwmGetWindowAttribute(HWND(handle), DWMWA_EXTENDED_FRAME_BOUNDS, rect_ptr, size);
+        }
+
+        let rect2 = Rectangle::from(rect2);
+
+        Rectangle {
+            x: rect1.x - rect2.x,
+            y: rect1.y - rect2.y,
+            width: rect1.width - rect2.width,
+            height: rect1.height - rect2.height,
+        }
+    }
+
+    fn is_minimized(&self, handle: isize) -> bool {
+        unsafe { IsIconic(HWND(handle)).as_bool() }
+    }
+
+    fn is_maximized(&self, handle: isize) -> bool {
+        unsafe { IsZoomed(HWND(handle)).as_bool() }
+    }
+
+    fn is_fullscreen(&self, handle: isize) -> bool {
+        let hwnd = HWND(handle);
+
+        unsafe {
+            let mut window_rect = RECT::default();
+            if GetWindowRect(hwnd, &mut window_rect).is_ok() {
+                let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+                let mut monitor_info = MONITORINFO {
+                    cbSize: size_of::<MONITORINFO>() as u32,
+                    ..Default::default()
+                };
+
+                if GetMonitorInfoW(monitor, &mut monitor_info).as_bool() {
+                    let screen_rect = monitor_info.rcMonitor;
+                    return screen_rect.left == window_rect.left
+                        && screen_rect.right == window_rect.right
+                        && screen_rect.top == window_rect.top
+                        && screen_rect.bottom == window_rect.bottom;
+                }
+            }
+        }
+
+        false
+    }
+
+    fn foreground_window(&self) -> isize {
+        unsafe { GetForegroundWindow().0 }
+    }
+
+    fn show(&self, handle: isize, command: ShowCommand) {
+        let command = match command {
+            ShowCommand::Hide => SW_HIDE,
+            ShowCommand::Normal => SW_SHOWNOACTIVATE,
+            ShowCommand::Minimize => SW_MINIMIZE,
+            ShowCommand::ShowMinimized => SW_SHOWMINIMIZED,
+            ShowCommand::ShowMaximized => SW_SHOWMAXIMIZED,
+        };
+
+        unsafe {
+            ShowWindow(HWND(handle), command);
+        }
+    }
+
+    fn set_window_positions(&self, positions: &[(isize, Rectangle)]) {
+        let flags =
+            SWP_FRAMECHANGED | SWP_NOACTIVATE | SWP_NOCOPYBITS | SWP_NOZORDER | SWP_NOOWNERZORDER;
+
+        let info = match unsafe { BeginDeferWindowPos(positions.len() as i32) } {
+            Ok(info) => info,
+            Err(e) => {
+                error!("Failed to BeginDeferWindowPos: {:?}", e);
+                return;
+            }
+        };
+
+        for (handle, rect) in positions {
+            unsafe {
+                let _ = DeferWindowPos(
+                    info,
+                    HWND(*handle),
+                    None,
+                    rect.x,
+                    rect.y,
+                    rect.width,
+                    rect.height,
+                    flags,
+                );
+            }
+        }
+
+        unsafe {
+            EndDeferWindowPos(info).unwrap_or_else(|e| {
+                error!("Failed to EndDeferWindowPos ({:?}): {:?}", info, e);
+            });
+        };
+    }
+
+    fn focus(&self, handle: isize) {
+        unsafe {
+            SetForegroundWindow(HWND(handle));
+        }
+    }
+
+    fn bring_to_top(&self, handle: isize) {
+        unsafe {
+            let _ = BringWindowToTop(HWND(handle));
+        }
+    }
+
+    fn close(&self, handle: isize) {
+        unsafe {
+            let _ =
+                SendNotifyMessageW(HWND(handle), WM_SYSCOMMAND, WPARAM(SC_CLOSE as usize), None);
+        }
+    }
+
+    fn screens(&self) -> Vec<Screen> {
+        Screen::all_screens()
+    }
+
+    fn screen_from_point(&self, point: Point) -> Screen {
+        Screen::from_point(point)
+    }
+
+    fn screen_from_rectangle(&self, rect: Rectangle) -> Screen {
+        Screen::from_rectangle(rect)
+    }
+
+    fn cursor_position(&self) -> Point {
+        let mut point = POINT::default();
+
+        unsafe {
+            let _ = GetCursorPos(&mut point);
+        }
+
+        Point {
+            x: point.x,
+            y: point.y,
+        }
+    }
+}
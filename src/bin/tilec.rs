@@ -0,0 +1,320 @@
+// Drives the running tile-rs from shells and scripts through its IPC server
+#[path = "../ipc/client.rs"]
+mod client;
+
+use crate::client::Client;
+use serde_json::{json, Value};
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: tilec [--json] <command>
+
+Commands:
+  layout next|previous|reset
+  focus left|right|up|down|next|previous|primary
+  swap next|previous|primary
+  primary shrink|expand|more|fewer
+  gaps increase|decrease|toggle
+  workspace <number>          switch to a workspace, numbered from 1
+  move workspace <number>     move the focused window to a workspace
+  move monitor <number>       move the focused window to a monitor
+  float                       toggle tiling the focused window
+  close                       close the focused window
+  reload                      reload the config file
+  query windows|focused|workspaces|monitors|state
+  subscribe [<event>...]      print events as JSON lines, every kind when none are named:
+                              window_created, window_destroyed, window_focused,
+                              window_moved, layout_changed, workspace_switched,
+                              monitor_focused
+
+Options:
+  --json      print the response as JSON
+  -h, --help  print this help
+
+Exit codes:
+  0  done
+  1  tile-rs refused the request
+  2  invalid arguments
+  3  tile-rs isn't reachable";
+
+const EXIT_REFUSED: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_UNREACHABLE: u8 = 3;
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        // a closed stdout, like `tilec --help | head`, isn't worth failing over
+        let _ = writeln!(io::stdout(), "{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let json = args.iter().any(|arg| arg == "--json");
+    args.retain(|arg| arg != "--json");
+
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+    let request = match parse(&args) {
+        Ok(request) => request,
+        Err(e) => {
+            eprintln!("tilec: {}\n\n{}", e, USAGE);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    let path = client::path();
+    let connected = Client::connect(&path)
+        .and_then(|mut client| Ok((client.request::<_, Value>(&request)?, client)));
+    let (response, mut client) = match connected {
+        Ok(connected) => connected,
+        Err(e) => {
+            eprintln!("tilec: {:#}, is tile-rs running?", e);
+            return ExitCode::from(EXIT_UNREACHABLE);
+        }
+    };
+
+    let mut stdout = io::stdout().lock();
+    let _ = if json {
+        writeln!(stdout, "{}", response)
+    } else {
+        print(&response, &mut stdout)
+    };
+
+    if response["type"] == "error" {
+        return ExitCode::from(EXIT_REFUSED);
+    }
+
+    // events keep coming until tile-rs exits or stdout is closed
+    if request.get("subscribe").is_some() {
+        loop {
+            match client.receive::<Value>() {
+                Ok(event) => {
+                    if writeln!(stdout, "{}", event)
+                        .and_then(|_| stdout.flush())
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    eprintln!("tilec: {:#}", e);
+                    return ExitCode::from(EXIT_UNREACHABLE);
+                }
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+// the request for the command line, in the server's JSON protocol
+fn parse(args: &[&str]) -> Result<Value, String> {
+    let action = match args {
+        ["layout", "next"] => json!("NextLayoutEngine"),
+        ["layout", "previous"] => json!("PreviousLayoutEngine"),
+        ["layout", "reset"] => json!("ResetLayout"),
+        ["focus", "next"] => json!("FocusNextWindow"),
+        ["focus", "previous"] => json!("FocusPreviousWindow"),
+        ["focus", "primary"] => json!("FocusPrimaryWindow"),
+        ["focus", direction @ ("left" | "right" | "up" | "down")] => {
+            json!({ "FocusWindowInDirection": capitalize(direction) })
+        }
+        ["swap", "next"] => json!("SwapFocusAndNextWindow"),
+        ["swap", "previous"] => json!("SwapFocusAndPreviousWindow"),
+        ["swap", "primary"] => json!("SwapFocusAndPrimaryWindow"),
+        ["primary", "shrink"] => json!("ShrinkPrimaryArea"),
+        ["primary", "expand"] => json!("ExpandPrimaryArea"),
+        ["primary", "more"] => json!("IncrementNumInPrimary"),
+        ["primary", "fewer"] => json!("DecrementNumInPrimary"),
+        ["gaps", "increase"] => json!("IncreaseGaps"),
+        ["gaps", "decrease"] => json!("DecreaseGaps"),
+        ["gaps", "toggle"] => json!("ToggleGaps"),
+        ["workspace", number] => json!({ "SwitchToWorkspace": index(number)? }),
+        ["move", "workspace", number] => {
+            json!({ "MoveFocusedWindowToWorkspace": index(number)? })
+        }
+        ["move", "monitor", number] => json!({ "MoveFocusedWindowToMonitor": index(number)? }),
+        ["float"] => json!("ToggleFocusedWindowTiling"),
+        ["close"] => json!("CloseFocusedWindow"),
+        ["reload"] => json!("ReloadConfig"),
+        ["query", "windows"] => return Ok(json!({ "query": "windows" })),
+        ["query", "focused"] => return Ok(json!({ "query": "focused_window" })),
+        ["query", "workspaces"] => return Ok(json!({ "query": "workspaces" })),
+        ["query", "monitors"] => return Ok(json!({ "query": "monitors" })),
+        ["query", "state"] => return Ok(json!({ "query": "state" })),
+        ["subscribe", kinds @ ..] => return Ok(json!({ "subscribe": kinds })),
+        [] => return Err("Missing command".to_string()),
+        _ => return Err(format!("Unknown command '{}'", args.join(" "))),
+    };
+
+    Ok(json!({ "command": action }))
+}
+
+// the zero based index the server takes for a number as displayed
+fn index(number: &str) -> Result<usize, String> {
+    match number.parse::<usize>() {
+        Ok(number) if number >= 1 => Ok(number - 1),
+        _ => Err(format!("'{}' isn't a number from 1 up", number)),
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+fn print(response: &Value, out: &mut impl Write) -> io::Result<()> {
+    let data = &response["data"];
+
+    match response["type"].as_str() {
+        Some("ok") => {}
+        Some("error") => eprintln!("tilec: {}", data.as_str().unwrap_or_default()),
+        Some("windows") => {
+            for window in data.as_array().into_iter().flatten() {
+                writeln!(out, "{}", window_line(window))?;
+            }
+        }
+        Some("focused_window") if data.is_null() => writeln!(out, "No window is focused")?,
+        Some("focused_window") => writeln!(out, "{}", window_line(data))?,
+        Some("workspaces") => {
+            for workspace in data.as_array().into_iter().flatten() {
+                let windows = workspace["windows"].as_array().map_or(0, Vec::len);
+                let monitor = match workspace["monitor"].as_u64() {
+                    Some(index) => format!("monitor {}", index + 1),
+                    None => "hidden".to_string(),
+                };
+
+                writeln!(
+                    out,
+                    "{} {}  {}  {}  {} window(s)",
+                    marker(workspace),
+                    text(&workspace["name"]),
+                    text(&workspace["layout"]),
+                    monitor,
+                    windows
+                )?;
+            }
+        }
+        Some("monitors") => {
+            for monitor in data.as_array().into_iter().flatten() {
+                let area = &monitor["working_area"];
+
+                writeln!(
+                    out,
+                    "{} {}  {}  {}x{} at {},{}  workspace {}",
+                    marker(monitor),
+                    monitor["index"].as_u64().unwrap_or_default() + 1,
+                    text(&monitor["name"]),
+                    area["width"],
+                    area["height"],
+                    area["x"],
+                    area["y"],
+                    text(&monitor["workspace"])
+                )?;
+            }
+        }
+        // the snapshot is too nested for a line each, it's made for reading as JSON
+        Some("state") => writeln!(out, "{:#}", data)?,
+        _ => writeln!(out, "{}", response)?,
+    }
+
+    Ok(())
+}
+
+fn window_line(window: &Value) -> String {
+    let flags: Vec<_> = ["focused", "floating", "minimized"]
+        .into_iter()
+        .filter(|flag| window[flag] == true)
+        .collect();
+
+    let mut line = format!(
+        "0x{:X}  {}  {}  {}",
+        window["handle"].as_i64().unwrap_or_default(),
+        text(&window["workspace"]),
+        text(&window["process"]),
+        text(&window["title"])
+    );
+
+    if !flags.is_empty() {
+        line += &format!("  ({})", flags.join(", "));
+    }
+
+    line
+}
+
+fn marker(value: &Value) -> &str {
+    if value["focused"] == true {
+        "*"
+    } else {
+        " "
+    }
+}
+
+fn text(value: &Value) -> &str {
+    value.as_str().unwrap_or("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_become_requests() {
+        assert_eq!(
+            parse(&["layout", "next"]),
+            Ok(json!({ "command": "NextLayoutEngine" }))
+        );
+        assert_eq!(
+            parse(&["focus", "left"]),
+            Ok(json!({ "command": { "FocusWindowInDirection": "Left" } }))
+        );
+        assert_eq!(
+            parse(&["workspace", "3"]),
+            Ok(json!({ "command": { "SwitchToWorkspace": 2 } }))
+        );
+        assert_eq!(
+            parse(&["query", "windows"]),
+            Ok(json!({ "query": "windows" }))
+        );
+        assert_eq!(parse(&["reload"]), Ok(json!({ "command": "ReloadConfig" })));
+        assert_eq!(
+            parse(&["subscribe", "workspace_switched"]),
+            Ok(json!({ "subscribe": ["workspace_switched"] }))
+        );
+    }
+
+    #[test]
+    fn bad_arguments_are_named() {
+        assert_eq!(parse(&[]), Err("Missing command".to_string()));
+        assert_eq!(
+            parse(&["focus", "sideways"]),
+            Err("Unknown command 'focus sideways'".to_string())
+        );
+        assert_eq!(
+            parse(&["workspace", "0"]),
+            Err("'0' isn't a number from 1 up".to_string())
+        );
+    }
+
+    #[test]
+    fn windows_print_on_one_line() {
+        let window = json!({
+            "handle": 0x1A2B,
+            "title": "Untitled - Notepad",
+            "process": "notepad.exe",
+            "workspace": "1",
+            "focused": true,
+            "floating": true,
+            "minimized": false,
+        });
+
+        assert_eq!(
+            window_line(&window),
+            "0x1A2B  1  notepad.exe  Untitled - Notepad  (focused, floating)"
+        );
+    }
+}
@@ -1,15 +1,65 @@
+use crate::classes::direction::Direction;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
-#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Deserialize, Serialize)]
+// Monitor and workspace indices are zero based, they are displayed one based
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Hash, Deserialize, Serialize)]
 pub enum Action {
     ToggleFocusedWindowTiling,
+    IncreaseGaps,
+    DecreaseGaps,
+    ToggleGaps,
+    FocusNextWindow,
+    FocusPreviousWindow,
+    FocusPrimaryWindow,
+    FocusWindowInDirection(Direction),
+    SwapFocusAndNextWindow,
+    SwapFocusAndPreviousWindow,
+    SwapFocusAndPrimaryWindow,
+    NextLayoutEngine,
+    PreviousLayoutEngine,
+    ShrinkPrimaryArea,
+    ExpandPrimaryArea,
+    IncrementNumInPrimary,
+    DecrementNumInPrimary,
+    ResetLayout,
+    CloseFocusedWindow,
+    MoveFocusedWindowToMonitor(usize),
+    SwitchToWorkspace(usize),
+    MoveFocusedWindowToWorkspace(usize),
+    ReloadConfig,
 }
 
 impl Display for Action {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let action = match self {
-            Action::ToggleFocusedWindowTiling => "Toggle Focused Window Tiling",
+            Action::ToggleFocusedWindowTiling => "Toggle Focused Window Tiling".to_string(),
+            Action::IncreaseGaps => "Increase Gaps".to_string(),
+            Action::DecreaseGaps => "Decrease Gaps".to_string(),
+            Action::ToggleGaps => "Toggle Gaps".to_string(),
+            Action::FocusNextWindow => "Focus Next Window".to_string(),
+            Action::FocusPreviousWindow => "Focus Previous Window".to_string(),
+            Action::FocusPrimaryWindow => "Focus Primary Window".to_string(),
+            Action::FocusWindowInDirection(direction) => format!("Focus Window {}", direction),
+            Action::SwapFocusAndNextWindow => "Swap Focus And Next Window".to_string(),
+            Action::SwapFocusAndPreviousWindow => "Swap Focus And Previous Window".to_string(),
+            Action::SwapFocusAndPrimaryWindow => "Swap Focus And Primary Window".to_string(),
+            Action::NextLayoutEngine => "Next Layout Engine".to_string(),
+            Action::PreviousLayoutEngine => "Previous Layout Engine".to_string(),
+            Action::ShrinkPrimaryArea => "Shrink Primary Area".to_string(),
+            Action::ExpandPrimaryArea => "Expand Primary Area".to_string(),
+            Action::IncrementNumInPrimary => "Increment Number In Primary".to_string(),
+            Action::DecrementNumInPrimary => "Decrement Number In Primary".to_string(),
+            Action::ResetLayout => "Reset Layout".to_string(),
+            Action::CloseFocusedWindow => "Close Focused Window".to_string(),
+            Action::MoveFocusedWindowToMonitor(index) => {
+                format!("Move Focused Window To Monitor {}", index + 1)
+            }
+            Action::SwitchToWorkspace(index) => format!("Switch To Workspace {}", index + 1),
+            Action::MoveFocusedWindowToWorkspace(index) => {
+                format!("Move Focused Window To Workspace {}", index + 1)
+            }
+            Action::ReloadConfig => "Reload Config".to_string(),
         };
         write!(f, "{}", action)
     }
@@ -0,0 +1,231 @@
+use crate::classes::window_location::WindowLocation;
+use crate::classes::window_state::WindowState;
+use crate::csharp::structs::Rectangle;
+use serde::{Deserialize, Serialize};
+
+// the widest gap, in pixels, beyond it tiles collapse on small monitors
+pub const MAX_GAP: i32 = 200;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Padding {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Padding {
+    #[allow(dead_code)]
+    pub fn uniform(size: i32) -> Self {
+        Self {
+            left: size,
+            top: size,
+            right: size,
+            bottom: size,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Gaps {
+    pub enabled: bool,
+    pub inner: i32,
+    pub outer: Padding,
+    pub step: i32,
+}
+
+impl Default for Gaps {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            inner: 0,
+            outer: Padding::default(),
+            step: 2,
+        }
+    }
+}
+
+impl Gaps {
+    pub fn increase(&mut self) {
+        self.inner = (self.inner + self.step).min(MAX_GAP);
+    }
+
+    pub fn decrease(&mut self) {
+        self.inner = (self.inner - self.step).max(0);
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    // shrink every normal tile by half the inner gap towards its neighbours and by the
+    // outer padding on the edges touching the layout area
+    pub fn apply(&self, locations: Vec<WindowLocation>, area: Rectangle) -> Vec<WindowLocation> {
+        if !self.enabled {
+            return locations;
+        }
+
+        let half = self.inner / 2;
+        let rest = self.inner - half;
+
+        locations
+            .into_iter()
+            .map(|location| {
+                if !matches!(location.state, WindowState::Normal) {
+                    return location;
+                }
+
+                let right = location.x + location.width;
+                let bottom = location.y + location.height;
+
+                let left = location.x
+                    + if location.x <= area.x {
+                        self.outer.left
+                    } else {
+                        half
+                    };
+                let top = location.y
+                    + if location.y <= area.y {
+                        self.outer.top
+                    } else {
+                        half
+                    };
+                let right = right
+                    - if right >= area.right() {
+                        self.outer.right
+                    } else {
+                        rest
+                    };
+                let bottom = bottom
+                    - if bottom >= area.bottom() {
+                        self.outer.bottom
+                    } else {
+                        rest
+                    };
+
+                WindowLocation::new(
+                    left,
+                    top,
+                    (right - left).max(1),
+                    (bottom - top).max(1),
+                    location.state,
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout_engines::rects;
+
+    fn tile(x: i32, y: i32, width: i32, height: i32) -> WindowLocation {
+        WindowLocation::new(x, y, width, height, WindowState::Normal)
+    }
+
+    #[test]
+    fn inner_gaps_split_between_neighbours() {
+        let gaps = Gaps {
+            inner: 10,
+            ..Default::default()
+        };
+
+        let layout = gaps.apply(
+            vec![
+                tile(0, 0, 500, 800),
+                tile(500, 0, 500, 400),
+                tile(500, 400, 500, 400),
+            ],
+            Rectangle::new(0, 0, 1000, 800),
+        );
+
+        assert_eq!(
+            rects(&layout),
+            [(0, 0, 495, 800), (505, 0, 495, 395), (505, 405, 495, 395)]
+        );
+    }
+
+    #[test]
+    fn outer_padding_applies_per_edge() {
+        let gaps = Gaps {
+            inner: 4,
+            outer: Padding {
+                left: 10,
+                top: 20,
+                right: 30,
+                bottom: 40,
+            },
+            ..Default::default()
+        };
+
+        let layout = gaps.apply(
+            vec![tile(0, 0, 500, 800), tile(500, 0, 500, 800)],
+            Rectangle::new(0, 0, 1000, 800),
+        );
+
+        assert_eq!(rects(&layout), [(10, 20, 488, 740), (502, 20, 468, 740)]);
+    }
+
+    #[test]
+    fn edges_follow_area_origin() {
+        let gaps = Gaps {
+            inner: 10,
+            outer: Padding::uniform(5),
+            ..Default::default()
+        };
+
+        let layout = gaps.apply(
+            vec![tile(-2560, -360, 1280, 1400), tile(-1280, -360, 1280, 1400)],
+            Rectangle::new(-2560, -360, 2560, 1400),
+        );
+
+        assert_eq!(
+            rects(&layout),
+            [(-2555, -355, 1270, 1390), (-1275, -355, 1270, 1390)]
+        );
+    }
+
+    #[test]
+    fn disabled_gaps_and_minimized_windows_are_untouched() {
+        let mut gaps = Gaps {
+            inner: 10,
+            outer: Padding::uniform(10),
+            ..Default::default()
+        };
+
+        let minimized = WindowLocation::new(0, 0, 1000, 800, WindowState::Minimized);
+        let layout = gaps.apply(vec![minimized], Rectangle::new(0, 0, 1000, 800));
+        assert_eq!(rects(&layout), [(0, 0, 1000, 800)]);
+
+        gaps.toggle();
+        let layout = gaps.apply(vec![tile(0, 0, 1000, 800)], Rectangle::new(0, 0, 1000, 800));
+        assert_eq!(rects(&layout), [(0, 0, 1000, 800)]);
+    }
+
+    #[test]
+    fn decrease_stops_at_zero() {
+        let mut gaps = Gaps::default();
+        gaps.increase();
+        assert_eq!(gaps.inner, 2);
+
+        gaps.decrease();
+        gaps.decrease();
+        assert_eq!(gaps.inner, 0);
+    }
+
+    #[test]
+    fn increase_stops_at_max() {
+        let mut gaps = Gaps {
+            inner: MAX_GAP - 1,
+            ..Default::default()
+        };
+        gaps.increase();
+        assert_eq!(gaps.inner, MAX_GAP);
+
+        gaps.increase();
+        assert_eq!(gaps.inner, MAX_GAP);
+    }
+}
@@ -2,13 +2,190 @@ use crate::classes::action::Action;
 use crate::classes::keys::{Keys, VirtualKey};
 use std::collections::HashMap;
 
+const NUMBER_KEYS: [VirtualKey; 9] = [
+    VirtualKey::Key1,
+    VirtualKey::Key2,
+    VirtualKey::Key3,
+    VirtualKey::Key4,
+    VirtualKey::Key5,
+    VirtualKey::Key6,
+    VirtualKey::Key7,
+    VirtualKey::Key8,
+    VirtualKey::Key9,
+];
+
+const MONITOR_KEYS: [VirtualKey; 3] = [VirtualKey::W, VirtualKey::E, VirtualKey::R];
+
+// chords Windows handles before any hook sees them, or that users expect to keep
+const RESERVED_KEYS: [(&str, &str); 5] = [
+    ("win+l", "locks the computer"),
+    ("ctrl+alt+Delete", "opens the security screen"),
+    ("ctrl+shift+Esc", "opens the task manager"),
+    ("alt+Tab", "switches windows"),
+    ("alt+F4", "closes the focused window"),
+];
+
+fn alt(key: VirtualKey) -> Keys {
+    Keys {
+        alt: true,
+        key,
+        ..Default::default()
+    }
+}
+
+fn alt_shift(key: VirtualKey) -> Keys {
+    Keys {
+        alt: true,
+        shift: true,
+        key,
+        ..Default::default()
+    }
+}
+
+// for the actions whose alt chords apps use themselves, like alt+Space for the window menu
+fn ctrl_alt(key: VirtualKey) -> Keys {
+    Keys {
+        ctrl: true,
+        ..alt(key)
+    }
+}
+
+fn ctrl_alt_shift(key: VirtualKey) -> Keys {
+    Keys {
+        ctrl: true,
+        ..alt_shift(key)
+    }
+}
+
 pub fn default_key_bindings() -> HashMap<Action, Keys> {
-    HashMap::from([(
-        Action::ToggleFocusedWindowTiling,
-        Keys {
-            alt: true,
-            key: VirtualKey::T,
-            ..Default::default()
-        },
-    )])
+    let mut key_bindings = HashMap::from([
+        (Action::ToggleFocusedWindowTiling, alt(VirtualKey::T)),
+        (Action::IncreaseGaps, alt_shift(VirtualKey::Equals)),
+        (Action::DecreaseGaps, alt_shift(VirtualKey::Minus)),
+        (Action::ToggleGaps, alt_shift(VirtualKey::G)),
+        (Action::FocusNextWindow, ctrl_alt(VirtualKey::J)),
+        (Action::FocusPreviousWindow, ctrl_alt(VirtualKey::K)),
+        (Action::FocusPrimaryWindow, alt(VirtualKey::M)),
+        (Action::SwapFocusAndNextWindow, alt_shift(VirtualKey::J)),
+        (Action::SwapFocusAndPreviousWindow, alt_shift(VirtualKey::K)),
+        (
+            Action::SwapFocusAndPrimaryWindow,
+            ctrl_alt(VirtualKey::Enter),
+        ),
+        (Action::NextLayoutEngine, ctrl_alt(VirtualKey::Space)),
+        (
+            Action::PreviousLayoutEngine,
+            ctrl_alt_shift(VirtualKey::Space),
+        ),
+        (Action::ShrinkPrimaryArea, ctrl_alt(VirtualKey::H)),
+        (Action::ExpandPrimaryArea, ctrl_alt(VirtualKey::L)),
+        (Action::IncrementNumInPrimary, alt(VirtualKey::Comma)),
+        (Action::DecrementNumInPrimary, alt(VirtualKey::Period)),
+        (Action::ResetLayout, alt(VirtualKey::N)),
+        (Action::CloseFocusedWindow, alt_shift(VirtualKey::C)),
+        (Action::ReloadConfig, alt(VirtualKey::Q)),
+    ]);
+
+    for (index, key) in MONITOR_KEYS.into_iter().enumerate() {
+        key_bindings.insert(Action::MoveFocusedWindowToMonitor(index), alt_shift(key));
+    }
+
+    for (index, key) in NUMBER_KEYS.into_iter().enumerate() {
+        key_bindings.insert(Action::SwitchToWorkspace(index), alt(key));
+        key_bindings.insert(Action::MoveFocusedWindowToWorkspace(index), alt_shift(key));
+    }
+
+    key_bindings
+}
+
+// every pair of actions a single chord would fire, each pair ordered and listed once
+pub fn conflicts(key_bindings: &HashMap<Action, Keys>) -> Vec<(Action, Action)> {
+    let mut bindings: Vec<_> = key_bindings.iter().collect();
+    bindings.sort();
+
+    let mut conflicts = Vec::new();
+    for (index, (action, keys)) in bindings.iter().enumerate() {
+        for (other, other_keys) in &bindings[index + 1..] {
+            if keys.overlaps(other_keys) {
+                conflicts.push((**action, **other));
+            }
+        }
+    }
+
+    conflicts
+}
+
+// what Windows does with `keys` instead of passing them on, if it's reserved
+pub fn reserved(keys: &Keys) -> Option<&'static str> {
+    RESERVED_KEYS
+        .iter()
+        .find(|(reserved, _)| reserved.parse::<Keys>().is_ok_and(|r| r.overlaps(keys)))
+        .map(|(_, reason)| *reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_key_bindings_are_unique() {
+        let key_bindings = default_key_bindings();
+
+        for (action, keys) in key_bindings.iter() {
+            let duplicates: Vec<_> = key_bindings
+                .iter()
+                .filter(|(other, other_keys)| *other != action && *other_keys == keys)
+                .map(|(other, _)| other)
+                .collect();
+
+            assert!(
+                duplicates.is_empty(),
+                "{} clashes with {:?}",
+                action,
+                duplicates
+            );
+        }
+    }
+
+    #[test]
+    fn default_key_bindings_are_not_reserved() {
+        for (action, keys) in default_key_bindings() {
+            assert_eq!(reserved(&keys), None, "{} uses {}", action, keys);
+        }
+    }
+
+    #[test]
+    fn overlapping_chords_conflict() {
+        let mut key_bindings = HashMap::from([
+            (Action::ResetLayout, "alt+n".parse().unwrap()),
+            (Action::FocusNextWindow, "lalt+n".parse().unwrap()),
+            (Action::FocusPreviousWindow, "ralt+n".parse().unwrap()),
+            (Action::CloseFocusedWindow, "alt+shift+n".parse().unwrap()),
+        ]);
+
+        assert_eq!(
+            conflicts(&key_bindings),
+            [
+                (Action::FocusNextWindow, Action::ResetLayout),
+                (Action::FocusPreviousWindow, Action::ResetLayout),
+            ]
+        );
+
+        key_bindings.remove(&Action::ResetLayout);
+        assert!(conflicts(&key_bindings).is_empty());
+        assert!(conflicts(&default_key_bindings()).is_empty());
+    }
+
+    #[test]
+    fn reserved_chords_are_named() {
+        assert_eq!(
+            reserved(&"win+L".parse().unwrap()),
+            Some("locks the computer")
+        );
+        assert_eq!(
+            reserved(&"lctrl+alt+delete".parse().unwrap()),
+            Some("opens the security screen")
+        );
+        assert_eq!(reserved(&"win+shift+l".parse().unwrap()), None);
+    }
 }
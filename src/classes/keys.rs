@@ -1,21 +1,248 @@
+use anyhow::{anyhow, bail, Context, Result};
+#[cfg(windows)]
 use log::trace;
-use serde::{Deserialize, Serialize};
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+#[cfg(windows)]
 use windows::Win32::Foundation::{LPARAM, WPARAM};
+#[cfg(windows)]
 use windows::Win32::UI::Input::KeyboardAndMouse::{
-    GetAsyncKeyState, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT,
+    GetAsyncKeyState, VIRTUAL_KEY, VK_LCONTROL, VK_LMENU, VK_LSHIFT, VK_LWIN, VK_RCONTROL,
+    VK_RMENU, VK_RSHIFT, VK_RWIN,
 };
+#[cfg(windows)]
 use windows::Win32::UI::WindowsAndMessaging::KBDLLHOOKSTRUCT;
+#[cfg(windows)]
 use windows::Win32::UI::WindowsAndMessaging::{WM_KEYDOWN, WM_SYSKEYDOWN};
 
-#[derive(Debug, Default, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+// Which of a modifier's two keys, only pressed keys are ever held on both sides
+#[derive(Debug, Default, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub enum Side {
+    #[default]
+    Either,
+    Left,
+    Right,
+    #[allow(dead_code)]
+    Both,
+}
+
+impl Side {
+    pub fn variants() -> [Side; 3] {
+        [Side::Either, Side::Left, Side::Right]
+    }
+
+    // the side held of a modifier, if any
+    #[allow(dead_code)]
+    pub fn held(left: bool, right: bool) -> Option<Side> {
+        match (left, right) {
+            (true, true) => Some(Side::Both),
+            (true, false) => Some(Side::Left),
+            (false, true) => Some(Side::Right),
+            (false, false) => None,
+        }
+    }
+
+    // whether holding `pressed` satisfies a binding asking for this side
+    pub fn accepts(&self, pressed: Side) -> bool {
+        match self {
+            Side::Either => true,
+            Side::Both => pressed == Side::Both,
+            side => pressed == *side || pressed == Side::Both,
+        }
+    }
+
+    // whether one chord can hold both sides
+    pub fn overlaps(&self, other: Side) -> bool {
+        self.accepts(other) || other.accepts(*self)
+    }
+
+    fn prefix(&self) -> &str {
+        match self {
+            Side::Left => "l",
+            Side::Right => "r",
+            Side::Either | Side::Both => "",
+        }
+    }
+}
+
+// Serialized as text like "win+ctrl+Left", see `FromStr`
+#[derive(Debug, Default, Copy, Clone, PartialEq, PartialOrd, Eq, Ord)]
 pub struct Keys {
     pub shift: bool,
     pub ctrl: bool,
     pub alt: bool,
     pub win: bool,
     pub key: VirtualKey,
+
+    // only looked at while the modifier is held
+    pub shift_side: Side,
+    pub ctrl_side: Side,
+    pub alt_side: Side,
+    pub win_side: Side,
 }
 
+impl Keys {
+    fn modifiers(&self) -> [(bool, Side, &str); 4] {
+        [
+            (self.win, self.win_side, "win"),
+            (self.ctrl, self.ctrl_side, "ctrl"),
+            (self.alt, self.alt_side, "alt"),
+            (self.shift, self.shift_side, "shift"),
+        ]
+    }
+
+    // whether these bound keys fire for the `pressed` keys, a modifier bound to
+    // `Side::Either` is satisfied by either of its keys
+    pub fn matches(&self, pressed: &Keys) -> bool {
+        self.key == pressed.key
+            && self.modifiers().iter().zip(pressed.modifiers()).all(
+                |(&(held, side, _), (pressed_held, pressed_side, _))| {
+                    held == pressed_held && (!held || side.accepts(pressed_side))
+                },
+            )
+    }
+
+    // the same chord with each held modifier accepting either of its keys
+    pub fn either_side(&self) -> Keys {
+        Keys {
+            shift: self.shift,
+            ctrl: self.ctrl,
+            alt: self.alt,
+            win: self.win,
+            key: self.key,
+            ..Default::default()
+        }
+    }
+
+    // whether some pressed chord fires both bindings
+    pub fn overlaps(&self, other: &Keys) -> bool {
+        self.key == other.key
+            && self.modifiers().iter().zip(other.modifiers()).all(
+                |(&(held, side, _), (other_held, other_side, _))| {
+                    held == other_held && (!held || side.overlaps(other_side))
+                },
+            )
+    }
+}
+
+// the shape `Keys` was saved in before bindings were text
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct KeyFields {
+    shift: bool,
+    ctrl: bool,
+    alt: bool,
+    win: bool,
+    key: VirtualKey,
+}
+
+impl Display for Keys {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (held, side, name) in self.modifiers() {
+            if held {
+                write!(f, "{}{}+", side.prefix(), name)?;
+            }
+        }
+
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for Keys {
+    type Err = anyhow::Error;
+
+    // modifiers then a key, joined by '+' and in any case, e.g. "alt+shift+t", a modifier
+    // prefixed with 'l' or 'r' only matches that side, e.g. "ralt+h"
+    fn from_str(text: &str) -> Result<Self> {
+        let mut tokens: Vec<_> = text.split('+').map(str::trim).collect();
+        let key = tokens.pop().unwrap_or_default();
+
+        if key.is_empty() {
+            bail!("Missing key in \"{}\"", text);
+        }
+
+        let mut keys = Keys {
+            key: key.parse().with_context(|| format!("In \"{}\"", text))?,
+            ..Default::default()
+        };
+
+        for token in tokens {
+            let lower = token.to_ascii_lowercase();
+            let (side, name) = match lower.as_str() {
+                "shift" | "ctrl" | "control" | "alt" | "win" => (Side::Either, lower.as_str()),
+                _ if lower.starts_with('l') => (Side::Left, &lower[1..]),
+                _ if lower.starts_with('r') => (Side::Right, &lower[1..]),
+                _ => (Side::Either, lower.as_str()),
+            };
+
+            let (held, held_side) = match name {
+                "shift" => (&mut keys.shift, &mut keys.shift_side),
+                "ctrl" | "control" => (&mut keys.ctrl, &mut keys.ctrl_side),
+                "alt" => (&mut keys.alt, &mut keys.alt_side),
+                "win" => (&mut keys.win, &mut keys.win_side),
+                _ if token.is_empty() => bail!("Empty modifier in \"{}\"", text),
+                _ => bail!(
+                    "Unknown modifier '{}' in \"{}\", expected shift, ctrl, alt or win, \
+                     optionally prefixed with l or r",
+                    token,
+                    text
+                ),
+            };
+
+            if *held {
+                bail!("Modifier '{}' is repeated in \"{}\"", token, text);
+            }
+            *held = true;
+            *held_side = side;
+        }
+
+        Ok(keys)
+    }
+}
+
+impl Serialize for Keys {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Keys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeysVisitor;
+
+        impl<'de> Visitor<'de> for KeysVisitor {
+            type Value = Keys;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                f.write_str("keys like \"alt+shift+t\"")
+            }
+
+            fn visit_str<E: de::Error>(self, text: &str) -> Result<Keys, E> {
+                text.parse().map_err(|e| E::custom(format!("{:#}", e)))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Keys, A::Error> {
+                let fields = KeyFields::deserialize(MapAccessDeserializer::new(map))?;
+
+                Ok(Keys {
+                    shift: fields.shift,
+                    ctrl: fields.ctrl,
+                    alt: fields.alt,
+                    win: fields.win,
+                    key: fields.key,
+                    ..Default::default()
+                })
+            }
+        }
+
+        deserializer.deserialize_any(KeysVisitor)
+    }
+}
+
+#[cfg(windows)]
 impl Keys {
     pub unsafe fn new(n_code: i32, w_param: WPARAM, l_param: LPARAM) -> Option<Self> {
         if n_code >= 0 {
@@ -31,18 +258,23 @@ impl Keys {
                         &key
                     );
 
-                    let shift = GetAsyncKeyState(VK_SHIFT.0 as i32) & (1 << 15) != 0;
-                    let ctrl = GetAsyncKeyState(VK_CONTROL.0 as i32) & (1 << 15) != 0;
-                    let alt = GetAsyncKeyState(VK_MENU.0 as i32) & (1 << 15) != 0;
-                    let win = GetAsyncKeyState(VK_LWIN.0 as i32) & (1 << 15) != 0
-                        || GetAsyncKeyState(VK_RWIN.0 as i32) & (1 << 15) != 0;
+                    let down = |vk: VIRTUAL_KEY| GetAsyncKeyState(vk.0 as i32) & (1 << 15) != 0;
+
+                    let shift = Side::held(down(VK_LSHIFT), down(VK_RSHIFT));
+                    let ctrl = Side::held(down(VK_LCONTROL), down(VK_RCONTROL));
+                    let alt = Side::held(down(VK_LMENU), down(VK_RMENU));
+                    let win = Side::held(down(VK_LWIN), down(VK_RWIN));
 
                     Some(Keys {
-                        shift,
-                        ctrl,
-                        alt,
-                        win,
+                        shift: shift.is_some(),
+                        ctrl: ctrl.is_some(),
+                        alt: alt.is_some(),
+                        win: win.is_some(),
                         key,
+                        shift_side: shift.unwrap_or_default(),
+                        ctrl_side: ctrl.unwrap_or_default(),
+                        alt_side: alt.unwrap_or_default(),
+                        win_side: win.unwrap_or_default(),
                     })
                 }
                 _ => None,
@@ -203,6 +435,7 @@ pub enum VirtualKey {
 }
 
 impl VirtualKey {
+    #[allow(dead_code)]
     pub fn from_vk(vk: u32) -> Self {
         match vk {
             0x08 => VirtualKey::Backspace,
@@ -353,7 +586,51 @@ impl VirtualKey {
     }
 }
 
+// digits are written bare, everything else by its variant name
+impl Display for VirtualKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = format!("{:?}", self);
+
+        match name.strip_prefix("Key") {
+            Some(digit) if digit.len() == 1 => f.write_str(digit),
+            _ => f.write_str(&name),
+        }
+    }
+}
+
+impl FromStr for VirtualKey {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        VirtualKey::variants()
+            .into_iter()
+            .find(|key| {
+                key.to_string().eq_ignore_ascii_case(name)
+                    || format!("{:?}", key).eq_ignore_ascii_case(name)
+            })
+            .ok_or_else(|| anyhow!("Unknown key '{}'", name))
+    }
+}
+
 impl VirtualKey {
+    // keys that only ever hold down a modifier
+    pub fn is_modifier(&self) -> bool {
+        matches!(
+            self,
+            VirtualKey::Shift
+                | VirtualKey::Ctrl
+                | VirtualKey::Alt
+                | VirtualKey::LeftWin
+                | VirtualKey::RightWin
+                | VirtualKey::LeftShift
+                | VirtualKey::RightShift
+                | VirtualKey::LeftControl
+                | VirtualKey::RightControl
+                | VirtualKey::LeftAlt
+                | VirtualKey::RightAlt
+        )
+    }
+
     pub fn variants() -> [VirtualKey; 144] {
         [
             VirtualKey::Unknown,
@@ -503,3 +780,136 @@ impl VirtualKey {
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        format!("{:#}", text.parse::<Keys>().unwrap_err())
+    }
+
+    #[test]
+    fn every_key_round_trips() {
+        for key in VirtualKey::variants() {
+            let keys = Keys {
+                win: true,
+                shift: true,
+                key,
+                ..Default::default()
+            };
+            let text = keys.to_string();
+
+            assert_eq!(text.parse::<Keys>().unwrap(), keys);
+            assert_eq!(text.to_uppercase().parse::<Keys>().unwrap(), keys);
+            assert_eq!(text.to_lowercase().parse::<Keys>().unwrap(), keys);
+
+            let name = format!("{:?}", key).to_lowercase();
+            assert_eq!(name.parse::<VirtualKey>().unwrap(), key);
+        }
+    }
+
+    #[test]
+    fn parses_modifiers_in_any_order_and_case() {
+        assert_eq!(
+            "alt+shift+t".parse::<Keys>().unwrap(),
+            Keys {
+                alt: true,
+                shift: true,
+                key: VirtualKey::T,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            "Ctrl + WIN + left".parse::<Keys>().unwrap().to_string(),
+            "win+ctrl+Left"
+        );
+        assert_eq!("alt+F12".parse::<Keys>().unwrap().key, VirtualKey::F12);
+        assert_eq!("alt+1".parse::<Keys>().unwrap().key, VirtualKey::Key1);
+        assert_eq!("alt".parse::<Keys>().unwrap().key, VirtualKey::Alt);
+    }
+
+    #[test]
+    fn errors_name_the_bad_token() {
+        assert_eq!(
+            error("alt+shift+tee"),
+            "In \"alt+shift+tee\": Unknown key 'tee'"
+        );
+        assert_eq!(
+            error("hyper+t"),
+            "Unknown modifier 'hyper' in \"hyper+t\", expected shift, ctrl, alt or win, \
+             optionally prefixed with l or r"
+        );
+        assert_eq!(error("alt+"), "Missing key in \"alt+\"");
+        assert_eq!(error("alt++t"), "Empty modifier in \"alt++t\"");
+        assert_eq!(
+            error("alt+ALT+t"),
+            "Modifier 'ALT' is repeated in \"alt+ALT+t\""
+        );
+    }
+
+    #[test]
+    fn serializes_as_text_and_reads_the_old_fields() {
+        let keys: Keys = serde_json::from_str("\"alt+shift+Equals\"").unwrap();
+        assert_eq!(
+            serde_json::to_string(&keys).unwrap(),
+            "\"alt+shift+Equals\""
+        );
+
+        let old: Keys =
+            serde_json::from_str(r#"{ "alt": true, "shift": true, "key": "Equals" }"#).unwrap();
+        assert_eq!(old, keys);
+    }
+
+    #[test]
+    fn sided_modifiers_round_trip() {
+        let keys: Keys = "RAlt+lshift+h".parse().unwrap();
+
+        assert!(keys.alt && keys.shift);
+        assert_eq!((keys.alt_side, keys.shift_side), (Side::Right, Side::Left));
+        assert_eq!(keys.to_string(), "ralt+lshift+H");
+        assert_eq!(keys.to_string().parse::<Keys>().unwrap(), keys);
+
+        for side in Side::variants() {
+            let keys = Keys {
+                win: true,
+                win_side: side,
+                key: VirtualKey::Left,
+                ..Default::default()
+            };
+            assert_eq!(keys.to_string().parse::<Keys>().unwrap(), keys);
+        }
+
+        assert_eq!(
+            error("xalt+h"),
+            "Unknown modifier 'xalt' in \"xalt+h\", expected shift, ctrl, alt or win, \
+             optionally prefixed with l or r"
+        );
+    }
+
+    #[test]
+    fn either_side_matches_both_keys() {
+        let pressed = |alt_side| Keys {
+            alt: true,
+            alt_side,
+            key: VirtualKey::H,
+            ..Default::default()
+        };
+        let alt: Keys = "alt+h".parse().unwrap();
+        let right_alt: Keys = "ralt+h".parse().unwrap();
+
+        for side in [Side::Left, Side::Right, Side::Both] {
+            assert!(alt.matches(&pressed(side)));
+        }
+
+        assert!(right_alt.matches(&pressed(Side::Right)));
+        assert!(right_alt.matches(&pressed(Side::Both)));
+        assert!(!right_alt.matches(&pressed(Side::Left)));
+
+        assert!(!alt.matches(&Keys {
+            shift: true,
+            ..pressed(Side::Left)
+        }));
+        assert!(!"h".parse::<Keys>().unwrap().matches(&pressed(Side::Left)));
+    }
+}
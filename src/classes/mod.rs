@@ -1,8 +1,12 @@
 pub mod action;
+pub mod direction;
+pub mod gaps;
 pub mod key_bindings;
 pub mod keys;
 pub mod monitor;
 pub mod native_monitor_container;
 pub mod window_location;
 pub mod window_order;
+pub mod window_rules;
 pub mod window_state;
+pub mod workspace_layout;
@@ -1,24 +1,26 @@
+use crate::backends::{default_backend, WindowBackend};
 use crate::classes::monitor::Monitor;
-use crate::csharp::screen::Screen;
 use crate::csharp::structs::{Point, Rectangle};
 use log::debug;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct NativeMonitorContainer {
+    backend: Arc<dyn WindowBackend>,
     pub monitors: Vec<Monitor>,
     pub focused_monitor: usize,
 }
 
 impl Default for NativeMonitorContainer {
     fn default() -> Self {
-        Self::new()
+        Self::new(default_backend())
     }
 }
 
 impl NativeMonitorContainer {
     #[allow(dead_code)]
-    pub fn new() -> Self {
-        let mut screens = Screen::all_screens();
+    pub fn new(backend: Arc<dyn WindowBackend>) -> Self {
+        let mut screens = backend.screens();
         screens.sort_by_key(|s| !s.primary);
 
         debug!("screens: {:?}", screens);
@@ -32,6 +34,7 @@ impl NativeMonitorContainer {
         debug!("monitors: {:?}", monitors);
 
         Self {
+            backend,
             monitors,
             focused_monitor: 0,
         }
@@ -58,7 +61,7 @@ impl NativeMonitorContainer {
 
     #[allow(dead_code)]
     pub fn get_monitor_at_point(&self, x: i32, y: i32) -> &Monitor {
-        let screen = Screen::from_point(Point { x, y });
+        let screen = self.backend.screen_from_point(Point { x, y });
         let monitor = self
             .monitors
             .iter()
@@ -72,7 +75,9 @@ impl NativeMonitorContainer {
 
     #[allow(dead_code)]
     pub fn get_monitor_at_rect(&self, x: i32, y: i32, width: i32, height: i32) -> &Monitor {
-        let screen = Screen::from_rectangle(Rectangle::new(x, y, width, height));
+        let screen = self
+            .backend
+            .screen_from_rectangle(Rectangle::new(x, y, width, height));
         let monitor = self
             .monitors
             .iter()
@@ -1,7 +1,8 @@
 use crate::classes::window_state::WindowState;
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WindowLocation {
     pub x: i32,
     pub y: i32,
@@ -21,6 +22,11 @@ impl WindowLocation {
         }
     }
 
+    pub fn offset(&mut self, dx: i32, dy: i32) {
+        self.x += dx;
+        self.y += dy;
+    }
+
     #[allow(dead_code)]
     pub fn is_point_inside(&self, x: i32, y: i32) -> bool {
         self.x <= x && x <= self.x + self.width && self.y <= y && y <= self.y + self.height
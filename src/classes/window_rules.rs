@@ -0,0 +1,509 @@
+use crate::csharp::structs::Size;
+use crate::window::Window;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+const IGNORE_WINDOW_CLASSES: [&str; 10] = [
+    "TaskManagerWindow",
+    "MSCTFIME UI",
+    "SHELLDLL_DefView",
+    "LockScreenBackstopFrame",
+    "Progman",
+    "Shell_TrayWnd",
+    "WorkerW",
+    "Shell_SecondaryTrayWnd",
+    "Windows.UI.Composition.DesktopWindowContentBridge",
+    "IME",
+];
+
+const IGNORE_PROGRAMS: [&str; 3] = [
+    "StartMenuExperienceHost.exe",
+    "SearchHost.exe",
+    "ApplicationFrameHost.exe",
+];
+
+const IGNORE_WINDOW_TITLES: [&str; 1] = ["Windows Input Experience"];
+
+lazy_static! {
+    // The shell is never managed, whatever the configured rules say
+    static ref SHELL_RULES: Vec<WindowRule> = {
+        let classes = IGNORE_WINDOW_CLASSES.map(|class| WindowRule {
+            class: Some(Pattern::Exact(class.to_string())),
+            ..WindowRule::ignore()
+        });
+        let processes = IGNORE_PROGRAMS.map(|process| WindowRule {
+            process: Some(Pattern::Exact(process.to_string())),
+            ..WindowRule::ignore()
+        });
+        let titles = IGNORE_WINDOW_TITLES.map(|title| WindowRule {
+            title: Some(Pattern::Exact(title.to_string())),
+            ..WindowRule::ignore()
+        });
+
+        classes.into_iter().chain(processes).chain(titles).collect()
+    };
+}
+
+// The rules deciding how new windows are managed, checked in order against every window
+// after the shell ones
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "StoredWindowRules")]
+pub struct WindowRules {
+    pub matching: Matching,
+    pub rules: Vec<WindowRule>,
+}
+
+// The rules as they are read, with the ignore lists they replaced
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StoredWindowRules {
+    matching: Matching,
+    rules: Vec<WindowRule>,
+
+    ignore_classes: Vec<String>,
+    ignore_processes: Vec<String>,
+    ignore_titles: Vec<String>,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Matching {
+    // the first matching rule decides everything
+    #[default]
+    First,
+    // every matching rule applies, later ones overriding the actions they set
+    Last,
+}
+
+// Matches when every given pattern does, so a rule without any matches every window.
+// The actions left out are left to the other rules.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowRule {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class: Option<Pattern>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process: Option<Pattern>, // the file name, like notepad.exe
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<Pattern>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>, // by name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<usize>, // by index, from 0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>, // in the layout order, 0 is the primary area
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<Size>, // floats the window at this size
+}
+
+// Written as `{ exact = "..." }`, `{ contains = "..." }`, `{ glob = "..." }` or `{ regex = "..." }`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pattern {
+    Exact(String),
+    Contains(String),
+    Glob(String), // `*` for any text, `?` for any one character
+    Regex(RegexPattern),
+}
+
+// A regex compiled once when the rules are read, so invalid ones fail the config
+#[derive(Debug, Clone)]
+pub struct RegexPattern(Regex);
+
+// What the matching rules ask of a window
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RuleActions {
+    pub ignore: bool,
+    pub float: bool,
+    pub workspace: Option<String>,
+    pub monitor: Option<usize>,
+    pub position: Option<usize>,
+    pub size: Option<Size>,
+}
+
+// the ignore lists become ignore rules ahead of the others, leaving out the shell
+impl From<StoredWindowRules> for WindowRules {
+    fn from(stored: StoredWindowRules) -> Self {
+        let classes = stored.ignore_classes.into_iter().map(|class| WindowRule {
+            class: Some(Pattern::Exact(class)),
+            ..WindowRule::ignore()
+        });
+        let processes = stored
+            .ignore_processes
+            .into_iter()
+            .map(|process| WindowRule {
+                process: Some(Pattern::Exact(process)),
+                ..WindowRule::ignore()
+            });
+        let titles = stored.ignore_titles.into_iter().map(|title| WindowRule {
+            title: Some(Pattern::Exact(title)),
+            ..WindowRule::ignore()
+        });
+
+        let ignored = classes
+            .chain(processes)
+            .chain(titles)
+            .filter(|rule| !SHELL_RULES.contains(rule));
+
+        Self {
+            matching: stored.matching,
+            rules: ignored.chain(stored.rules).collect(),
+        }
+    }
+}
+
+impl WindowRules {
+    pub fn actions(&self, class: &str, process: &str, title: &str) -> RuleActions {
+        if SHELL_RULES
+            .iter()
+            .any(|rule| rule.matches(class, process, title))
+        {
+            return WindowRule::ignore().apply(RuleActions::default());
+        }
+
+        let mut matched = self
+            .rules
+            .iter()
+            .filter(|rule| rule.matches(class, process, title));
+
+        match self.matching {
+            Matching::First => matched
+                .next()
+                .map(|rule| rule.apply(RuleActions::default()))
+                .unwrap_or_default(),
+            Matching::Last => {
+                matched.fold(RuleActions::default(), |actions, rule| rule.apply(actions))
+            }
+        }
+    }
+
+    pub fn actions_for(&self, window: &Window) -> RuleActions {
+        self.actions(&window.class(), window.process_file_name(), &window.title())
+    }
+
+    pub fn ignores(&self, window: &Window) -> bool {
+        self.actions_for(window).ignore
+    }
+}
+
+impl WindowRule {
+    // a rule ignoring the windows it matches, to fill in the patterns of
+    pub fn ignore() -> Self {
+        Self {
+            ignore: Some(true),
+            ..Default::default()
+        }
+    }
+
+    // `is_none_or` needs a newer Rust than this builds with
+    #[allow(clippy::unnecessary_map_or)]
+    pub fn matches(&self, class: &str, process: &str, title: &str) -> bool {
+        [
+            (&self.class, class),
+            (&self.process, process),
+            (&self.title, title),
+        ]
+        .into_iter()
+        .all(|(pattern, text)| pattern.as_ref().map_or(true, |p| p.matches(text)))
+    }
+
+    fn apply(&self, actions: RuleActions) -> RuleActions {
+        RuleActions {
+            ignore: self.ignore.unwrap_or(actions.ignore),
+            float: self.float.unwrap_or(actions.float),
+            workspace: self.workspace.clone().or(actions.workspace),
+            monitor: self.monitor.or(actions.monitor),
+            position: self.position.or(actions.position),
+            size: self.size.or(actions.size),
+        }
+    }
+}
+
+impl RuleActions {
+    // a fixed size can't be tiled
+    pub fn floats(&self) -> bool {
+        self.float || self.size.is_some()
+    }
+}
+
+impl Pattern {
+    pub fn matches(&self, text: &str) -> bool {
+        match self {
+            Pattern::Exact(exact) => text == exact,
+            Pattern::Contains(part) => text.contains(part.as_str()),
+            Pattern::Glob(glob) => glob_matches(glob, text),
+            Pattern::Regex(regex) => regex.0.is_match(text),
+        }
+    }
+}
+
+// `*` matches any run of characters and `?` any single one, everything else itself
+fn glob_matches(glob: &str, text: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut g, mut t) = (0, 0);
+    // where the last `*` was and the text it's covering up to
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match glob.get(g) {
+            Some('*') => {
+                star = Some((g, t));
+                g += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                g += 1;
+                t += 1;
+            }
+            _ => match star {
+                // let the `*` take one more character and try again
+                Some((star_g, star_t)) => {
+                    star = Some((star_g, star_t + 1));
+                    g = star_g + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    glob[g..].iter().all(|&c| c == '*')
+}
+
+impl RegexPattern {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Regex::new(pattern).map(Self)
+    }
+}
+
+impl PartialEq for RegexPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Eq for RegexPattern {}
+
+impl Serialize for RegexPattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for RegexPattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Self::new(&pattern).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(class: Pattern) -> WindowRule {
+        WindowRule {
+            class: Some(class),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn patterns_match_exactly_partly_by_glob_or_regex() {
+        assert!(Pattern::Exact("Notepad".to_string()).matches("Notepad"));
+        assert!(!Pattern::Exact("Notepad".to_string()).matches("Notepad++"));
+        assert!(
+            Pattern::Contains("Visual Studio".to_string()).matches("main.rs - Visual Studio Code")
+        );
+
+        let glob = Pattern::Glob("Shell_*TrayWnd".to_string());
+        assert!(glob.matches("Shell_TrayWnd"));
+        assert!(glob.matches("Shell_SecondaryTrayWnd"));
+        assert!(!glob.matches("Shell_TrayWndX"));
+        assert!(Pattern::Glob("?otepad*".to_string()).matches("Notepad++"));
+        assert!(Pattern::Glob("*a*b".to_string()).matches("xaxxab"));
+        assert!(!Pattern::Glob("a?".to_string()).matches("a"));
+
+        let regex = Pattern::Regex(RegexPattern::new(r"(?i)^steam\.exe$").unwrap());
+        assert!(regex.matches("Steam.exe"));
+        assert!(!regex.matches("steamwebhelper.exe"));
+    }
+
+    #[test]
+    fn rules_match_when_every_pattern_does() {
+        let rule = WindowRule {
+            process: Some(Pattern::Exact("firefox.exe".to_string())),
+            title: Some(Pattern::Contains("Picture-in-Picture".to_string())),
+            ..Default::default()
+        };
+
+        assert!(rule.matches("MozillaDialogClass", "firefox.exe", "Picture-in-Picture"));
+        assert!(!rule.matches("MozillaWindowClass", "firefox.exe", "Mozilla Firefox"));
+        assert!(WindowRule::default().matches("Any", "any.exe", "Any"));
+    }
+
+    #[test]
+    fn first_match_stops_at_the_first_rule() {
+        let rules = WindowRules {
+            matching: Matching::First,
+            rules: vec![
+                WindowRule {
+                    float: Some(true),
+                    ..rule(Pattern::Exact("Dialog".to_string()))
+                },
+                WindowRule {
+                    workspace: Some("3".to_string()),
+                    ..rule(Pattern::Glob("*".to_string()))
+                },
+            ],
+        };
+
+        assert_eq!(
+            rules.actions("Dialog", "app.exe", "Open"),
+            RuleActions {
+                float: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            rules.actions("Main", "app.exe", "App").workspace.as_deref(),
+            Some("3")
+        );
+    }
+
+    #[test]
+    fn last_match_layers_the_matching_rules() {
+        let rules = WindowRules {
+            matching: Matching::Last,
+            rules: vec![
+                WindowRule {
+                    float: Some(true),
+                    monitor: Some(1),
+                    ..rule(Pattern::Glob("*".to_string()))
+                },
+                WindowRule {
+                    float: Some(false),
+                    position: Some(0),
+                    ..rule(Pattern::Exact("Editor".to_string()))
+                },
+                WindowRule {
+                    size: Some(Size {
+                        width: 800,
+                        height: 600,
+                    }),
+                    ..rule(Pattern::Exact("Palette".to_string()))
+                },
+            ],
+        };
+
+        assert_eq!(
+            rules.actions("Editor", "code.exe", "main.rs"),
+            RuleActions {
+                monitor: Some(1),
+                position: Some(0),
+                ..Default::default()
+            }
+        );
+
+        let palette = rules.actions("Palette", "code.exe", "Colors");
+        assert!(palette.floats());
+        assert_eq!(palette.monitor, Some(1));
+    }
+
+    #[test]
+    fn the_shell_is_ignored_whatever_the_rules() {
+        let rules = WindowRules {
+            matching: Matching::Last,
+            rules: vec![WindowRule {
+                ignore: Some(false),
+                workspace: Some("2".to_string()),
+                ..rule(Pattern::Glob("*".to_string()))
+            }],
+        };
+
+        assert!(rules.actions("Shell_TrayWnd", "explorer.exe", "").ignore);
+        assert!(rules.actions("Any", "SearchHost.exe", "Search").ignore);
+        assert!(
+            rules
+                .actions("Any", "any.exe", "Windows Input Experience")
+                .ignore
+        );
+        assert_eq!(
+            rules
+                .actions("Notepad", "notepad.exe", "Untitled")
+                .workspace
+                .as_deref(),
+            Some("2")
+        );
+        assert!(WindowRules::default().rules.is_empty());
+    }
+
+    #[test]
+    fn ignore_lists_become_rules() {
+        let rules: WindowRules = toml::from_str(
+            r#"
+            ignore_classes = ["Progman", "Chrome_WidgetWin_1"]
+            ignore_processes = ["SearchHost.exe"]
+            ignore_titles = ["Picture-in-Picture"]
+
+            [[rules]]
+            float = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(rules.rules.len(), 3);
+        assert!(
+            rules
+                .actions("Chrome_WidgetWin_1", "chrome.exe", "New Tab")
+                .ignore
+        );
+        assert!(
+            rules
+                .actions("Any", "firefox.exe", "Picture-in-Picture")
+                .ignore
+        );
+        assert!(rules.actions("Notepad", "notepad.exe", "Untitled").float);
+        assert!(toml::from_str::<WindowRules>("ignore = []").is_err());
+    }
+
+    #[test]
+    fn rules_read_from_toml() {
+        let rules: WindowRules = toml::from_str(
+            r##"
+            matching = "last"
+
+            [[rules]]
+            process = { regex = '(?i)^steam\.exe$' }
+            workspace = "4"
+
+            [[rules]]
+            class = { glob = "#32770" }
+            title = { contains = "Properties" }
+            size = { width = 400, height = 500 }
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(rules.matching, Matching::Last);
+        assert_eq!(rules.rules.len(), 2);
+        assert_eq!(
+            rules
+                .actions("Main", "steam.exe", "Steam")
+                .workspace
+                .as_deref(),
+            Some("4")
+        );
+        assert_eq!(toml::from_str(&toml::to_string(&rules).unwrap()), Ok(rules));
+
+        let invalid = toml::from_str::<WindowRules>("[[rules]]\ntitle = { regex = '(' }");
+        assert!(invalid.is_err());
+    }
+}
@@ -0,0 +1,11 @@
+use crate::layout_engines::LayoutEngineType;
+use serde::{Deserialize, Serialize};
+
+// Layout a workspace starts with, the primary area is left to the engine when unset
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceLayout {
+    pub layout_engine_type: LayoutEngineType,
+    pub num_in_primary: Option<i32>,
+    pub primary_percent: Option<f64>,
+}
@@ -0,0 +1,547 @@
+use crate::app::Settings;
+use crate::classes::action::Action;
+use crate::classes::gaps::MAX_GAP;
+use crate::classes::key_bindings::conflicts;
+use crate::classes::keys::Keys;
+use crate::APP_NAME;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub const CONFIG_FILE_NAME: &str = "tile-rs.toml";
+pub const CONFIG_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const JSON_CONFIG_FILE_NAME: &str = "tile-rs.json";
+
+// overrides where the config file lives
+const CONFIG_PATH_VARIABLE: &str = "TILE_RS_CONFIG";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => ConfigFormat::Json,
+            _ => ConfigFormat::Toml,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Binding {
+    pub action: Action,
+    pub keys: Keys,
+
+    // also hand the keys to the focused window
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub passthrough: bool,
+}
+
+// The human editable side of the settings, kept next to eframe's own storage
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub settings: Settings,
+    pub bindings: Vec<Binding>,
+}
+
+impl Config {
+    pub fn new(
+        settings: &Settings,
+        key_bindings: &HashMap<Action, Keys>,
+        passthrough: &HashSet<Action>,
+    ) -> Self {
+        let mut bindings: Vec<_> = key_bindings
+            .iter()
+            .map(|(action, keys)| Binding {
+                action: *action,
+                keys: *keys,
+                passthrough: passthrough.contains(action),
+            })
+            .collect();
+        bindings.sort_by_key(|binding| binding.action);
+
+        Self {
+            settings: settings.clone(),
+            bindings,
+        }
+    }
+
+    // `TILE_RS_CONFIG`, else the json file if there is one, else the toml file, both in
+    // eframe's storage directory
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os(CONFIG_PATH_VARIABLE) {
+            return Some(PathBuf::from(path));
+        }
+
+        let directory = eframe::storage_dir(APP_NAME)?;
+        let json = directory.join(JSON_CONFIG_FILE_NAME);
+
+        if json.exists() {
+            Some(json)
+        } else {
+            Some(directory.join(CONFIG_FILE_NAME))
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        Self::parse(&text, ConfigFormat::from_path(path))
+            .with_context(|| format!("Invalid config {}", path.display()))
+    }
+
+    // refuses to write a config that wouldn't load again
+    pub fn save(&self, path: &Path) -> Result<()> {
+        self.validate()
+            .with_context(|| format!("Not saved to {}", path.display()))?;
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let text = self.to_string(ConfigFormat::from_path(path))?;
+        fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn parse(text: &str, format: ConfigFormat) -> Result<Self> {
+        let mut unknown = Vec::new();
+        let mut track = |path: serde_ignored::Path| unknown.push(path.to_string());
+
+        let config: Config = match format {
+            ConfigFormat::Toml => {
+                serde_ignored::deserialize(toml::Deserializer::new(text), &mut track)?
+            }
+            ConfigFormat::Json => {
+                let mut deserializer = serde_json::Deserializer::from_str(text);
+                let config = serde_ignored::deserialize(&mut deserializer, &mut track)?;
+                deserializer.end()?;
+                config
+            }
+        };
+
+        if !unknown.is_empty() {
+            bail!("Unknown field(s): {}", unknown.join(", "));
+        }
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn to_string(&self, format: ConfigFormat) -> Result<String> {
+        Ok(match format {
+            ConfigFormat::Toml => toml::to_string_pretty(self)?,
+            ConfigFormat::Json => serde_json::to_string_pretty(self)?,
+        })
+    }
+
+    fn validate(&self) -> Result<()> {
+        let names = &self.settings.workspace_names;
+        if names.is_empty() {
+            bail!("settings.workspace_names: At least one workspace is required");
+        }
+
+        let mut seen = HashSet::new();
+        for name in names {
+            if name.trim().is_empty() {
+                bail!("settings.workspace_names: Names can't be empty");
+            }
+            if !seen.insert(name) {
+                bail!("settings.workspace_names: '{}' is listed twice", name);
+            }
+        }
+
+        if let Some(name) = self
+            .settings
+            .workspace_layouts
+            .keys()
+            .find(|name| !names.contains(name))
+        {
+            bail!("settings.workspace_layouts: No workspace named '{}'", name);
+        }
+
+        for (name, layout) in &self.settings.workspace_layouts {
+            if let Some(percent) = layout.primary_percent {
+                if !(0.0..=1.0).contains(&percent) {
+                    bail!(
+                        "settings.workspace_layouts.{}.primary_percent: {} is not between 0 and 1",
+                        name,
+                        percent
+                    );
+                }
+            }
+            if let Some(count) = layout.num_in_primary {
+                if count < 1 {
+                    bail!(
+                        "settings.workspace_layouts.{}.num_in_primary: {} is less than 1",
+                        name,
+                        count
+                    );
+                }
+            }
+        }
+
+        let gaps = &self.settings.gaps;
+        for (name, gap) in [
+            ("inner", gaps.inner),
+            ("outer.left", gaps.outer.left),
+            ("outer.top", gaps.outer.top),
+            ("outer.right", gaps.outer.right),
+            ("outer.bottom", gaps.outer.bottom),
+        ] {
+            if !(0..=MAX_GAP).contains(&gap) {
+                bail!(
+                    "settings.gaps.{}: {} is not between 0 and {}",
+                    name,
+                    gap,
+                    MAX_GAP
+                );
+            }
+        }
+
+        let mut seen = HashSet::new();
+        for binding in &self.bindings {
+            if !seen.insert(binding.action) {
+                bail!("bindings: {:?} is bound twice", binding.action);
+            }
+        }
+
+        let key_bindings = self.key_bindings();
+        if let Some((action, other)) = conflicts(&key_bindings).first() {
+            bail!(
+                "bindings: {:?} and {:?} are both bound to {}",
+                action,
+                other,
+                key_bindings[action]
+            );
+        }
+
+        Ok(())
+    }
+
+    // bindings keyed by action, missing actions are left to the caller
+    pub fn key_bindings(&self) -> HashMap<Action, Keys> {
+        self.bindings
+            .iter()
+            .map(|binding| (binding.action, binding.keys))
+            .collect()
+    }
+
+    pub fn passthrough(&self) -> HashSet<Action> {
+        self.bindings
+            .iter()
+            .filter(|binding| binding.passthrough)
+            .map(|binding| binding.action)
+            .collect()
+    }
+}
+
+// Notices edits to the config file by comparing its contents every `interval`
+pub struct ConfigWatcher {
+    path: PathBuf,
+    contents: Option<String>,
+    interval: Duration,
+    last_poll: Instant,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: PathBuf, interval: Duration) -> Self {
+        Self {
+            contents: fs::read_to_string(&path).ok(),
+            path,
+            interval,
+            last_poll: Instant::now(),
+        }
+    }
+
+    // true once for every change, a deleted file is not a change
+    pub fn poll(&mut self) -> bool {
+        if self.last_poll.elapsed() < self.interval {
+            return false;
+        }
+        self.last_poll = Instant::now();
+
+        let contents = fs::read_to_string(&self.path).ok();
+        if contents.is_none() || contents == self.contents {
+            return false;
+        }
+
+        self.contents = contents;
+        true
+    }
+
+    // takes the current contents as seen, so our own writes don't come back as edits
+    pub fn refresh(&mut self) {
+        self.contents = fs::read_to_string(&self.path).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::App;
+    use crate::backends::fake_backend::FakeBackend;
+    use crate::backends::simulated_desktop::dual_monitor_desktop;
+    use crate::classes::key_bindings::default_key_bindings;
+    use crate::classes::keys::VirtualKey;
+    use crate::classes::window_rules::{Pattern, WindowRule};
+    use crate::classes::workspace_layout::WorkspaceLayout;
+    use crate::context::context;
+    use crate::csharp::structs::Rectangle;
+    use crate::layout_engines::LayoutEngineType;
+    use std::sync::Arc;
+
+    fn config() -> Config {
+        let mut settings = Settings::default();
+        settings.gaps.inner = 8;
+        settings.window_rules.rules.push(WindowRule {
+            class: Some(Pattern::Exact("Steam".to_string())),
+            ..WindowRule::ignore()
+        });
+        settings.workspace_layouts.insert(
+            "2".to_string(),
+            WorkspaceLayout {
+                layout_engine_type: LayoutEngineType::Tall,
+                num_in_primary: Some(2),
+                primary_percent: Some(0.6),
+            },
+        );
+
+        Config::new(
+            &settings,
+            &default_key_bindings(),
+            &HashSet::from([Action::CloseFocusedWindow]),
+        )
+    }
+
+    #[test]
+    fn round_trips_through_toml_and_json() {
+        let config = config();
+
+        for format in [ConfigFormat::Toml, ConfigFormat::Json] {
+            let text = config.to_string(format).unwrap();
+            assert_eq!(Config::parse(&text, format).unwrap(), config);
+        }
+    }
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let config = Config::parse(
+            r#"
+                [settings.gaps]
+                inner = 4
+
+                [[bindings]]
+                action = { SwitchToWorkspace = 0 }
+                keys = "win+1"
+            "#,
+            ConfigFormat::Toml,
+        )
+        .unwrap();
+
+        assert_eq!(config.settings.gaps.inner, 4);
+        assert_eq!(config.settings.gaps.step, Settings::default().gaps.step);
+        assert_eq!(config.settings.workspace_names.len(), 9);
+        assert_eq!(
+            config.key_bindings()[&Action::SwitchToWorkspace(0)],
+            Keys {
+                win: true,
+                key: VirtualKey::Key1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_named() {
+        let error = Config::parse("[settings.gaps]\ninnr = 4\n", ConfigFormat::Toml)
+            .unwrap_err()
+            .to_string();
+
+        assert_eq!(error, "Unknown field(s): settings.gaps.innr");
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let error = Config::parse(
+            "[settings]\npanel_orientation = \"Diagonal\"\n",
+            ConfigFormat::Toml,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("Diagonal"), "{}", error);
+        assert!(error.contains("line 2"), "{}", error);
+
+        let error = Config::parse(
+            r#"{ "settings": { "workspace_names": ["1", "1"] } }"#,
+            ConfigFormat::Json,
+        )
+        .unwrap_err()
+        .to_string();
+        assert_eq!(error, "settings.workspace_names: '1' is listed twice");
+
+        let error = Config::parse("[settings.gaps.outer]\nleft = 500\n", ConfigFormat::Toml)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            error,
+            "settings.gaps.outer.left: 500 is not between 0 and 200"
+        );
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("tile-rs-{}-{}", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn app_creates_loads_and_rejects_config_files() {
+        let backend = Arc::new(FakeBackend::new());
+        let path = temp_path(CONFIG_FILE_NAME);
+
+        let app = App::with_backend(backend.clone()).with_config_path(Some(path.clone()));
+        assert_eq!(Config::load(&path).unwrap(), app.config());
+
+        fs::write(&path, "[settings.gaps]\ninner = 6\n").unwrap();
+        let app = App::with_backend(backend.clone()).with_config_path(Some(path.clone()));
+        assert_eq!(app.settings.gaps.inner, 6);
+        assert_eq!(app.key_bindings, default_key_bindings());
+        assert_eq!(app.config_error, None);
+
+        fs::write(&path, "[settings.gaps]\ninner = \"wide\"\n").unwrap();
+        let app = App::with_backend(backend).with_config_path(Some(path.clone()));
+        assert_eq!(app.settings, Settings::default());
+        assert!(app.config_error.unwrap().contains("inner"));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn watcher_reports_each_edit_once() {
+        let path = temp_path("watched.toml");
+        fs::write(&path, "").unwrap();
+
+        let mut watcher = ConfigWatcher::new(path.clone(), Duration::ZERO);
+        assert!(!watcher.poll());
+
+        fs::write(&path, "[settings]\n").unwrap();
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+
+        fs::write(&path, "[settings.gaps]\n").unwrap();
+        watcher.refresh();
+        assert!(!watcher.poll());
+
+        fs::remove_file(&path).unwrap();
+        assert!(!watcher.poll());
+    }
+
+    #[test]
+    fn conflicting_bindings_are_rejected() {
+        let error = Config::parse(
+            r#"
+                [[bindings]]
+                action = "ResetLayout"
+                keys = "alt+n"
+
+                [[bindings]]
+                action = "FocusNextWindow"
+                keys = "ralt+N"
+            "#,
+            ConfigFormat::Toml,
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert_eq!(
+            error,
+            "bindings: FocusNextWindow and ResetLayout are both bound to ralt+N"
+        );
+    }
+
+    #[test]
+    fn conflicting_bindings_are_not_saved() {
+        let path = temp_path("conflicting.toml");
+        let mut app =
+            App::with_backend(Arc::new(FakeBackend::new())).with_config_path(Some(path.clone()));
+        let saved = fs::read_to_string(&path).unwrap();
+
+        let keys = app.key_bindings[&Action::FocusNextWindow];
+        app.key_bindings.insert(Action::ResetLayout, keys);
+        app.save_config();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), saved);
+        assert!(app.config_error.unwrap().contains("both bound to"));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn config_reload_applies_changes_and_rejects_invalid_edits() {
+        let path = temp_path("reload.toml");
+
+        let mut desktop = dual_monitor_desktop();
+        let mut app = App::with_backend(desktop.backend()).with_config_path(Some(path.clone()));
+        context(&mut app);
+
+        let notepad = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        let steam = desktop.open_window(0, "Steam", "Steam", "steam.exe");
+        desktop.pump(&mut app);
+
+        let mut config = Config::load(&path).unwrap();
+        config.settings.workspace_layouts.insert(
+            "1".to_string(),
+            WorkspaceLayout {
+                layout_engine_type: LayoutEngineType::Tall,
+                ..Default::default()
+            },
+        );
+        config.settings.window_rules.rules.push(WindowRule {
+            class: Some(Pattern::Exact("Steam".to_string())),
+            ..WindowRule::ignore()
+        });
+        config.bindings.retain(|b| b.action != Action::ResetLayout);
+        config.bindings.push(Binding {
+            action: Action::ResetLayout,
+            keys: Keys {
+                ctrl: true,
+                key: VirtualKey::N,
+                ..Default::default()
+            },
+            passthrough: true,
+        });
+        config.save(&path).unwrap();
+
+        app.reload_config().unwrap();
+        desktop.pump(&mut app);
+
+        assert_eq!(app.config_error, None);
+        assert_eq!(app.windows_manager.windows.len(), 1);
+        assert_eq!(app.workspace_manager.workspace_for_window(steam), None);
+        assert_eq!(desktop.rect(notepad), Rectangle::new(0, 0, 1920, 1040));
+        assert_eq!(
+            app.workspace_manager.workspace_layouts()["1"].layout_engine_type,
+            LayoutEngineType::Tall
+        );
+        assert_eq!(app.key_bindings[&Action::ResetLayout].key, VirtualKey::N);
+        assert!(app.key_bindings[&Action::ResetLayout].ctrl);
+        assert!(desktop.press(app.key_bindings[&Action::ResetLayout]));
+
+        fs::write(&path, "[settings.gaps]\ninner = -\n").unwrap();
+        assert!(app.reload_config().is_err());
+        assert!(app.config_error.is_some());
+        assert_eq!(app.settings, config.settings);
+        assert_eq!(app.key_bindings, config.key_bindings());
+
+        fs::remove_file(path).unwrap();
+    }
+}
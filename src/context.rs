@@ -1,16 +1,13 @@
 use crate::app::App;
-use std::thread;
 
-pub fn context(mut app: &mut App) {
-    app.windows_manager.init(app.settings.layout_engine_type);
+pub fn context(app: &mut App) {
+    app.workspace_manager
+        .add_window_manager(&mut app.windows_manager);
+    app.workspace_manager
+        .create_workspaces(&app.settings.workspace_names);
 
-    app.windows_manager.event_window_created.subscribe();
-    app.workspace_manager.add_window_manager();
+    app.layout_completed = Some(app.workspace_manager.subscribe_layout_completed());
 
-    let test = app.windows_manager.event_window_updated.subscribe();
-    thread::spawn(move || {
-        for message in test.iter() {
-            println!("Received message: {:?}", message);
-        }
-    });
+    app.apply_settings();
+    app.windows_manager.init();
 }
@@ -1,3 +1,4 @@
 pub mod screen;
 pub mod structs;
+#[cfg(windows)]
 pub mod system_information;
@@ -1,36 +1,58 @@
-use crate::csharp::structs::{Point, Rectangle};
+#[cfg(windows)]
+use crate::csharp::structs::Point;
+use crate::csharp::structs::Rectangle;
+#[cfg(windows)]
 use crate::csharp::system_information;
+#[cfg(windows)]
 use crate::csharp::system_information::multi_monitor_support;
+#[cfg(windows)]
 use std::ffi::OsStr;
+#[cfg(windows)]
 use std::mem::size_of;
+#[cfg(windows)]
 use std::os::windows::ffi::OsStrExt;
+#[cfg(windows)]
 use std::ptr;
+#[cfg(windows)]
 use windows::core::PCWSTR;
+#[cfg(windows)]
 use windows::Win32::Foundation::{BOOL, FALSE, LPARAM, POINT, RECT, TRUE};
+#[cfg(windows)]
 use windows::Win32::Graphics::Gdi::{
     CreateDCW, DeleteDC, EnumDisplayMonitors, GetDeviceCaps, GetMonitorInfoW, MonitorFromPoint,
     MonitorFromRect, BITSPIXEL, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW,
     MONITOR_DEFAULTTONEAREST, PLANES,
 };
+#[cfg(windows)]
 use windows::Win32::UI::WindowsAndMessaging::MONITORINFOF_PRIMARY;
 
+#[cfg(windows)]
 const PRIMARY_MONITOR: isize = 0xBAADF00D;
 
+#[cfg(windows)]
 #[allow(dead_code)]
 struct MonitorData {
     pub hmonitor: HMONITOR,
     pub monitor_info: MONITORINFOEXW,
 }
 
-#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+#[derive(Debug, Default, Eq, PartialEq, Hash, Clone)]
 pub struct Screen {
     pub bounds: Rectangle,
     pub primary: bool,
     pub device_name: String,
     pub hmonitor: isize,
     pub bit_depth: i32,
+    pub working_area: Rectangle,
 }
 
+impl Screen {
+    pub fn working_area(&self) -> Rectangle {
+        self.working_area
+    }
+}
+
+#[cfg(windows)]
 impl Screen {
     pub fn new(monitor: isize, hdc: Option<HDC>) -> Screen {
         let mut screen_dc = hdc;
@@ -89,6 +111,7 @@ impl Screen {
             device_name,
             hmonitor: hmonitor.0,
             bit_depth,
+            working_area: Self::query_working_area(hmonitor.0),
         }
     }
 
@@ -142,8 +165,8 @@ impl Screen {
         FALSE
     }
 
-    pub fn working_area(&self) -> Rectangle {
-        if !multi_monitor_support() || self.hmonitor == PRIMARY_MONITOR {
+    fn query_working_area(hmonitor: isize) -> Rectangle {
+        if !multi_monitor_support() || hmonitor == PRIMARY_MONITOR {
             system_information::working_area()
         } else {
             let mut monitor_info = MONITORINFO {
@@ -152,7 +175,7 @@ impl Screen {
             };
 
             unsafe {
-                let _ = GetMonitorInfoW(HMONITOR(self.hmonitor), &mut monitor_info);
+                let _ = GetMonitorInfoW(HMONITOR(hmonitor), &mut monitor_info);
             };
 
             Rectangle::from(monitor_info.rcWork)
@@ -1,18 +1,20 @@
+use serde::{Deserialize, Serialize};
+#[cfg(windows)]
 use windows::Win32::Foundation::RECT;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Size {
     pub width: i32,
     pub height: i32,
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Rectangle {
     pub x: i32,
     pub y: i32,
@@ -84,6 +86,13 @@ impl Rectangle {
         self.y + self.height
     }
 
+    pub fn center(&self) -> Point {
+        Point {
+            x: self.x + self.width / 2,
+            y: self.y + self.height / 2,
+        }
+    }
+
     #[allow(dead_code)]
     pub fn is_empty(&self) -> bool {
         self.width == 0 && self.height == 0 && self.x == 0 && self.y == 0
@@ -158,6 +167,7 @@ impl Rectangle {
     }
 }
 
+#[cfg(windows)]
 impl From<RECT> for Rectangle {
     fn from(rect: RECT) -> Self {
         Self {
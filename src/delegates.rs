@@ -1,6 +1,11 @@
+use crate::classes::window_location::WindowLocation;
+use crate::classes::window_rules::RuleActions;
 use crate::window::Window;
 use crate::windows_manager::WindowUpdateType;
 
 pub type WindowDelegate = Window;
-pub type WindowCreateDelegate = (Window, bool);
+pub type WindowCreateDelegate = (Window, bool, RuleActions); // first create, the rules it matched
 pub type WindowUpdateDelegate = (Window, WindowUpdateType);
+pub type LayoutCompletedDelegate = (String, Vec<(isize, WindowLocation)>);
+pub type WorkspaceSwitchedDelegate = (usize, Option<usize>, usize); // monitor, from, to
+pub type MonitorDelegate = usize;
@@ -1,5 +1,6 @@
 use crossbeam_channel::{unbounded, Receiver, Sender};
 
+#[derive(Debug)]
 pub struct Event<T> {
     senders: Vec<Sender<T>>,
 }
@@ -17,6 +18,11 @@ impl<T: Clone> Event<T> {
         receiver
     }
 
+    // shares an existing subscription, so one receiver can follow several events
+    pub fn add_subscriber(&mut self, sender: Sender<T>) {
+        self.senders.push(sender);
+    }
+
     pub fn broadcast(&mut self, message: T) {
         self.senders
             .retain(|sender| sender.send(message.clone()).is_ok());
@@ -1,4 +1,5 @@
 pub mod event;
 pub mod single;
+#[cfg(windows)]
 pub mod win32_helpers;
 pub mod windows_defer_pos_handle;
@@ -1,15 +1,14 @@
+use crate::backends::{ShowCommand, WindowBackend};
 use crate::classes::window_location::WindowLocation;
 use crate::classes::window_state::WindowState;
+use crate::csharp::structs::Rectangle;
 use crate::window::Window;
-use windows::Win32::UI::WindowsAndMessaging::{
-    DeferWindowPos, EndDeferWindowPos, ShowWindow, HDWP, SWP_FRAMECHANGED, SWP_NOACTIVATE,
-    SWP_NOCOPYBITS, SWP_NOMOVE, SWP_NOOWNERZORDER, SWP_NOSIZE, SWP_NOZORDER, SW_MINIMIZE,
-    SW_SHOWMAXIMIZED, SW_SHOWNOACTIVATE,
-};
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct WindowsDeferPosHandle<'a> {
-    info: HDWP,
+    backend: Arc<dyn WindowBackend>,
+    positions: Vec<(isize, Rectangle)>,
     to_minimize: Vec<&'a Window>,
     to_maximize: Vec<&'a Window>,
     to_normal: Vec<&'a Window>,
@@ -17,9 +16,10 @@ pub struct WindowsDeferPosHandle<'a> {
 
 impl<'a> WindowsDeferPosHandle<'a> {
     #[allow(dead_code)]
-    pub fn new(info: HDWP) -> Self {
+    pub fn new(backend: Arc<dyn WindowBackend>, count: usize) -> Self {
         Self {
-            info,
+            backend,
+            positions: Vec::with_capacity(count),
             to_minimize: vec![],
             to_maximize: vec![],
             to_normal: vec![],
@@ -28,17 +28,14 @@ impl<'a> WindowsDeferPosHandle<'a> {
 
     #[allow(dead_code)]
     pub fn defer_window_pos(&mut self, window: &'a Window, location: &WindowLocation) {
-        let mut flags =
-            SWP_FRAMECHANGED | SWP_NOACTIVATE | SWP_NOCOPYBITS | SWP_NOZORDER | SWP_NOOWNERZORDER;
-
         match location.state {
             WindowState::Maximized => {
                 self.to_maximize.push(window);
-                flags |= SWP_NOMOVE | SWP_NOSIZE;
+                return;
             }
             WindowState::Minimized => {
                 self.to_minimize.push(window);
-                flags |= SWP_NOMOVE | SWP_NOSIZE;
+                return;
             }
             WindowState::Normal => {
                 self.to_normal.push(window);
@@ -57,33 +54,28 @@ impl<'a> WindowsDeferPosHandle<'a> {
             || old_location.width != width
             || old_location.height != height
         {
-            unsafe {
-                let _ = DeferWindowPos(self.info, window.hwnd(), None, x, y, width, height, flags);
-            }
+            self.positions
+                .push((window.handle, Rectangle::new(x, y, width, height)));
         }
     }
 }
 
 impl Drop for WindowsDeferPosHandle<'_> {
     fn drop(&mut self) {
-        self.to_minimize.iter().for_each(|w| unsafe {
+        self.to_minimize.iter().for_each(|w| {
             if !w.is_minimized() {
-                ShowWindow(w.hwnd(), SW_MINIMIZE);
+                self.backend.show(w.handle, ShowCommand::Minimize);
             }
         });
-        self.to_maximize.iter().for_each(|w| unsafe {
+        self.to_maximize.iter().for_each(|w| {
             if !w.is_maximized() {
-                ShowWindow(w.hwnd(), SW_SHOWMAXIMIZED);
+                self.backend.show(w.handle, ShowCommand::ShowMaximized);
             }
         });
-        self.to_normal.iter().for_each(|w| unsafe {
-            ShowWindow(w.hwnd(), SW_SHOWNOACTIVATE);
+        self.to_normal.iter().for_each(|w| {
+            self.backend.show(w.handle, ShowCommand::Normal);
         });
 
-        unsafe {
-            EndDeferWindowPos(self.info).unwrap_or_else(|e| {
-                log::error!("Failed to EndDeferWindowPos ({:?}): {:?}", self.info, e);
-            });
-        };
+        self.backend.set_window_positions(&self.positions);
     }
 }
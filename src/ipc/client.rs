@@ -0,0 +1,78 @@
+// Shared with the `tilec` binary, so this only uses std, serde and anyhow
+use anyhow::{bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+#[cfg(windows)]
+use std::fs::{File, OpenOptions};
+#[cfg(not(windows))]
+use std::os::unix::net::UnixStream;
+
+// overrides where the server listens
+const IPC_PATH_VARIABLE: &str = "TILE_RS_IPC";
+
+#[cfg(windows)]
+const PIPE_NAME: &str = r"\\.\pipe\tile-rs";
+
+#[cfg(not(windows))]
+const SOCKET_FILE_NAME: &str = "tile-rs.sock";
+
+#[cfg(windows)]
+type Stream = File;
+#[cfg(not(windows))]
+type Stream = UnixStream;
+
+// `TILE_RS_IPC`, else a named pipe on Windows and a socket in the temp directory elsewhere
+pub fn path() -> PathBuf {
+    if let Some(path) = std::env::var_os(IPC_PATH_VARIABLE) {
+        return PathBuf::from(path);
+    }
+
+    #[cfg(windows)]
+    return PathBuf::from(PIPE_NAME);
+
+    #[cfg(not(windows))]
+    return std::env::temp_dir().join(SOCKET_FILE_NAME);
+}
+
+// One connection to the running instance, requests are answered in the order sent
+#[allow(dead_code)]
+pub struct Client {
+    reader: BufReader<Stream>,
+    writer: Stream,
+}
+
+#[allow(dead_code)]
+impl Client {
+    pub fn connect(path: &Path) -> Result<Self> {
+        #[cfg(windows)]
+        let writer = OpenOptions::new().read(true).write(true).open(path);
+        #[cfg(not(windows))]
+        let writer = UnixStream::connect(path);
+
+        let writer = writer.with_context(|| format!("Failed to connect to {}", path.display()))?;
+        let reader = BufReader::new(writer.try_clone()?);
+
+        Ok(Self { reader, writer })
+    }
+
+    pub fn request<T: Serialize, R: DeserializeOwned>(&mut self, request: &T) -> Result<R> {
+        let text = serde_json::to_string(request)? + "\n";
+        self.writer.write_all(text.as_bytes())?;
+        self.writer.flush()?;
+
+        self.receive()
+    }
+
+    // the next line sent, after subscribing the events come one at a time
+    pub fn receive<R: DeserializeOwned>(&mut self) -> Result<R> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            bail!("The connection was closed before an answer came");
+        }
+
+        serde_json::from_str(&line).with_context(|| format!("Invalid response: {}", line.trim()))
+    }
+}
@@ -0,0 +1,222 @@
+use crate::app::App;
+use crate::delegates::{
+    MonitorDelegate, WindowCreateDelegate, WindowDelegate, WindowUpdateDelegate,
+    WorkspaceSwitchedDelegate,
+};
+use crate::ipc::protocol::EventMessage;
+use crate::windows_manager::{WindowUpdateType, WindowsManager};
+use crate::workspace_manager::WorkspaceManager;
+use crossbeam_channel::Receiver;
+
+// Turns what the managers broadcast into the messages IPC subscribers get
+pub struct EventFeed {
+    window_created: Receiver<WindowCreateDelegate>,
+    window_destroyed: Receiver<WindowDelegate>,
+    window_updated: Receiver<WindowUpdateDelegate>,
+    workspace_switched: Receiver<WorkspaceSwitchedDelegate>,
+    monitor_focused: Receiver<MonitorDelegate>,
+
+    // layout engines change from too many places to broadcast, they are compared instead
+    layouts: Vec<String>,
+    // except for the primary area, which the engines keep to themselves
+    layout_actions: Vec<String>,
+}
+
+impl EventFeed {
+    pub fn new(
+        windows_manager: &mut WindowsManager,
+        workspace_manager: &mut WorkspaceManager,
+    ) -> Self {
+        Self {
+            window_created: windows_manager.event_window_created.subscribe(),
+            window_destroyed: windows_manager.event_window_destroyed.subscribe(),
+            window_updated: windows_manager.event_window_updated.subscribe(),
+            workspace_switched: workspace_manager.event_workspace_switched.subscribe(),
+            monitor_focused: workspace_manager.event_monitor_focused.subscribe(),
+
+            layouts: Self::layouts(workspace_manager),
+            layout_actions: Vec::new(),
+        }
+    }
+
+    // a layout action ran on `workspace`
+    pub fn layout_action(&mut self, workspace: &str) {
+        if !self.layout_actions.iter().any(|name| name == workspace) {
+            self.layout_actions.push(workspace.to_string());
+        }
+    }
+
+    fn layouts(workspace_manager: &WorkspaceManager) -> Vec<String> {
+        workspace_manager
+            .workspace_container
+            .workspaces()
+            .iter()
+            .map(|workspace| workspace.layout_name())
+            .collect()
+    }
+
+    // everything that happened since the last poll
+    pub fn poll(&mut self, app: &App) -> Vec<EventMessage> {
+        let mut messages = Vec::new();
+        let container = &app.workspace_manager.workspace_container;
+        let name = |index| {
+            container
+                .get_workspace_at_index(index)
+                .map(|workspace| workspace.name.clone())
+        };
+
+        for (window, _, _) in self.window_created.try_iter() {
+            messages.push(EventMessage::WindowCreated {
+                window: app.window_info(&window),
+            });
+        }
+
+        for (window, update_type) in self.window_updated.try_iter() {
+            match update_type {
+                WindowUpdateType::Foreground => messages.push(EventMessage::WindowFocused {
+                    window: app.window_info(&window),
+                }),
+                WindowUpdateType::MoveEnd => messages.push(EventMessage::WindowMoved {
+                    window: app.window_info(&window),
+                }),
+                _ => {}
+            }
+        }
+
+        for window in self.window_destroyed.try_iter() {
+            messages.push(EventMessage::WindowDestroyed {
+                handle: window.handle,
+            });
+        }
+
+        for (monitor, previous, index) in self.workspace_switched.try_iter() {
+            if let Some(workspace) = name(index) {
+                messages.push(EventMessage::WorkspaceSwitched {
+                    monitor,
+                    workspace,
+                    previous: previous.and_then(name),
+                });
+            }
+        }
+
+        for monitor in self.monitor_focused.try_iter() {
+            let workspace = app
+                .workspace_manager
+                .monitor_container
+                .get_monitor_at_index(monitor)
+                .and_then(|monitor| container.get_workspace_for_monitor(monitor))
+                .and_then(name);
+
+            messages.push(EventMessage::MonitorFocused { monitor, workspace });
+        }
+
+        // workspaces created since the last poll have nothing to compare with
+        let mut changed = std::mem::take(&mut self.layout_actions);
+        let layouts = Self::layouts(&app.workspace_manager);
+        for (index, (layout, previous)) in layouts.iter().zip(&self.layouts).enumerate() {
+            if layout != previous {
+                if let Some(workspace) = name(index) {
+                    if !changed.contains(&workspace) {
+                        changed.push(workspace);
+                    }
+                }
+            }
+        }
+        self.layouts = layouts;
+
+        for workspace in changed {
+            let layout = container
+                .workspaces()
+                .iter()
+                .find(|w| w.name == workspace)
+                .map(|w| w.layout_name());
+
+            if let Some(layout) = layout {
+                messages.push(EventMessage::LayoutChanged { workspace, layout });
+            }
+        }
+
+        messages
+    }
+}
+
+// the client side is a Unix socket, a named pipe needs Win32 to connect
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use crate::backends::simulated_desktop::dual_monitor_desktop;
+    use crate::classes::action::Action;
+    use serde_json::{json, Value};
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    #[test]
+    fn subscribers_follow_windows_workspaces_and_layouts() {
+        let mut desktop = dual_monitor_desktop();
+        let path = std::env::temp_dir().join(format!("tile-rs-events-{}.sock", std::process::id()));
+        let mut app = desktop.app().with_ipc_path(path.clone());
+
+        let stream = UnixStream::connect(&path).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        (&stream)
+            .write_all(b"{\"subscribe\": [\"window_created\", \"workspace_switched\", \"layout_changed\"]}\n")
+            .unwrap();
+
+        let mut lines = BufReader::new(&stream).lines();
+        let mut next = || serde_json::from_str::<Value>(&lines.next().unwrap().unwrap()).unwrap();
+        assert_eq!(next(), json!({ "type": "ok" }));
+
+        let handle = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.pump(&mut app);
+
+        let created = next();
+        assert_eq!(created["event"], "window_created");
+        assert_eq!(created["window"]["handle"], handle);
+        assert_eq!(created["window"]["workspace"], "1");
+
+        app.handle_action(Action::SwitchToWorkspace(2));
+        desktop.pump(&mut app);
+        assert_eq!(
+            next(),
+            json!({ "event": "workspace_switched", "monitor": 0, "workspace": "3", "previous": "1" })
+        );
+
+        app.handle_action(Action::NextLayoutEngine);
+        desktop.pump(&mut app);
+        let layout = app
+            .workspace_manager
+            .focused_workspace_mut()
+            .unwrap()
+            .layout_name();
+        assert_eq!(
+            next(),
+            json!({ "event": "layout_changed", "workspace": "3", "layout": layout })
+        );
+
+        for action in [
+            Action::ShrinkPrimaryArea,
+            Action::IncrementNumInPrimary,
+            Action::ResetLayout,
+        ] {
+            app.handle_action(action);
+            desktop.pump(&mut app);
+            assert_eq!(
+                next(),
+                json!({ "event": "layout_changed", "workspace": "3", "layout": layout })
+            );
+        }
+
+        // one event for a frame, however many actions ran in it
+        app.handle_action(Action::ExpandPrimaryArea);
+        app.handle_action(Action::NextLayoutEngine);
+        desktop.pump(&mut app);
+        app.handle_action(Action::FocusNextWindow);
+        desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.pump(&mut app);
+
+        assert_eq!(next()["event"], "layout_changed");
+        assert_eq!(next()["event"], "window_created");
+    }
+}
@@ -0,0 +1,251 @@
+use crate::classes::action::Action;
+use crate::classes::monitor::Monitor;
+use crate::csharp::structs::Rectangle;
+use crate::snapshot::Snapshot;
+use crate::window::Window;
+use crate::workspace::Workspace;
+use serde::{Deserialize, Serialize};
+
+// One line of JSON each, like {"command": "FocusNextWindow"},
+// {"command": {"SwitchToWorkspace": 2}} or {"query": "windows"}.
+// {"subscribe": ["workspace_switched"]} turns the connection into a stream of
+// `EventMessage` lines after the first response, an empty list follows every kind.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Request {
+    Command(Action),
+    Query(Query),
+    Subscribe(Vec<EventKind>),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Query {
+    Windows,
+    FocusedWindow,
+    Workspaces,
+    Monitors,
+    State,
+}
+
+// Answered with one line each, like {"type": "ok"} or {"type": "error", "data": "..."}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum Response {
+    Ok,
+    Error(String),
+    Windows(Vec<WindowInfo>),
+    FocusedWindow(Option<WindowInfo>),
+    Workspaces(Vec<WorkspaceInfo>),
+    Monitors(Vec<MonitorInfo>),
+    State(Snapshot),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    WindowCreated,
+    WindowDestroyed,
+    WindowFocused,
+    WindowMoved,
+    LayoutChanged,
+    WorkspaceSwitched,
+    MonitorFocused,
+}
+
+// Streamed to subscribers one line each, like
+// {"event": "workspace_switched", "monitor": 0, "workspace": "2", "previous": "1"}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum EventMessage {
+    WindowCreated {
+        window: WindowInfo,
+    },
+    WindowDestroyed {
+        handle: isize,
+    },
+    WindowFocused {
+        window: WindowInfo,
+    },
+    // dropped after being dragged, the layout moves windows without these
+    WindowMoved {
+        window: WindowInfo,
+    },
+    LayoutChanged {
+        workspace: String,
+        layout: String,
+    },
+    WorkspaceSwitched {
+        monitor: usize,
+        workspace: String,
+        previous: Option<String>,
+    },
+    MonitorFocused {
+        monitor: usize,
+        workspace: Option<String>,
+    },
+}
+
+impl EventMessage {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            EventMessage::WindowCreated { .. } => EventKind::WindowCreated,
+            EventMessage::WindowDestroyed { .. } => EventKind::WindowDestroyed,
+            EventMessage::WindowFocused { .. } => EventKind::WindowFocused,
+            EventMessage::WindowMoved { .. } => EventKind::WindowMoved,
+            EventMessage::LayoutChanged { .. } => EventKind::LayoutChanged,
+            EventMessage::WorkspaceSwitched { .. } => EventKind::WorkspaceSwitched,
+            EventMessage::MonitorFocused { .. } => EventKind::MonitorFocused,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowInfo {
+    pub handle: isize,
+    pub title: String,
+    pub class: String,
+    pub process: String,
+    pub rect: Rectangle,
+    pub workspace: Option<String>,
+    pub focused: bool,
+    pub floating: bool,
+    pub minimized: bool,
+}
+
+impl WindowInfo {
+    pub fn new(window: &Window, workspace: Option<&Workspace>, floating: bool) -> Self {
+        let location = window.location();
+
+        Self {
+            handle: window.handle,
+            title: window.title(),
+            class: window.class(),
+            process: window.process_file_name().to_string(),
+            rect: Rectangle::new(location.x, location.y, location.width, location.height),
+            workspace: workspace.map(|workspace| workspace.name.clone()),
+            focused: window.is_focused(),
+            floating,
+            minimized: window.is_minimized(),
+        }
+    }
+}
+
+// Indices are zero based, like the ones `Action` takes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceInfo {
+    pub index: usize,
+    pub name: String,
+    pub layout: String,
+    pub monitor: Option<usize>,
+    pub focused: bool,
+    pub windows: Vec<isize>,
+}
+
+impl WorkspaceInfo {
+    pub fn new(index: usize, workspace: &Workspace, monitor: Option<usize>, focused: bool) -> Self {
+        Self {
+            index,
+            name: workspace.name.clone(),
+            layout: workspace.layout_name(),
+            monitor,
+            focused,
+            windows: workspace
+                .windows
+                .iter()
+                .map(|window| window.handle)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitorInfo {
+    pub index: usize,
+    pub name: String,
+    pub primary: bool,
+    pub bounds: Rectangle,
+    pub working_area: Rectangle,
+    pub workspace: Option<String>,
+    pub focused: bool,
+}
+
+impl MonitorInfo {
+    pub fn new(monitor: &Monitor, workspace: Option<&Workspace>, focused: bool) -> Self {
+        Self {
+            index: monitor.index,
+            name: monitor.name().to_string(),
+            primary: monitor.screen.primary,
+            bounds: monitor.screen.bounds,
+            working_area: monitor.screen.working_area(),
+            workspace: workspace.map(|workspace| workspace.name.clone()),
+            focused,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classes::direction::Direction;
+
+    #[test]
+    fn requests_read_as_plain_json() {
+        let requests = [
+            (
+                r#"{"command": "FocusNextWindow"}"#,
+                Request::Command(Action::FocusNextWindow),
+            ),
+            (
+                r#"{"command": {"SwitchToWorkspace": 2}}"#,
+                Request::Command(Action::SwitchToWorkspace(2)),
+            ),
+            (
+                r#"{"command": {"FocusWindowInDirection": "Left"}}"#,
+                Request::Command(Action::FocusWindowInDirection(Direction::Left)),
+            ),
+            (r#"{"query": "windows"}"#, Request::Query(Query::Windows)),
+            (
+                r#"{"query": "focused_window"}"#,
+                Request::Query(Query::FocusedWindow),
+            ),
+            (r#"{"query": "state"}"#, Request::Query(Query::State)),
+        ];
+
+        for (text, request) in requests {
+            assert_eq!(serde_json::from_str::<Request>(text).unwrap(), request);
+        }
+
+        assert!(serde_json::from_str::<Request>(r#"{"command": "Dance"}"#).is_err());
+    }
+
+    #[test]
+    fn events_are_flat() {
+        let message = EventMessage::WorkspaceSwitched {
+            monitor: 0,
+            workspace: "2".to_string(),
+            previous: Some("1".to_string()),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&message).unwrap(),
+            r#"{"event":"workspace_switched","monitor":0,"workspace":"2","previous":"1"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"subscribe": ["layout_changed"]}"#).unwrap(),
+            Request::Subscribe(vec![EventKind::LayoutChanged])
+        );
+    }
+
+    #[test]
+    fn responses_are_tagged() {
+        assert_eq!(
+            serde_json::to_string(&Response::Ok).unwrap(),
+            r#"{"type":"ok"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Response::Error("No workspace 10".to_string())).unwrap(),
+            r#"{"type":"error","data":"No workspace 10"}"#
+        );
+    }
+}
@@ -0,0 +1,448 @@
+use crate::helpers::event::Event;
+use crate::ipc::protocol::{EventKind, EventMessage, Request, Response};
+use anyhow::{bail, Context, Result};
+use crossbeam_channel::{Receiver, Sender};
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[cfg(not(windows))]
+use std::fs;
+#[cfg(not(windows))]
+use std::os::unix::fs::FileTypeExt;
+#[cfg(not(windows))]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+#[cfg(windows)]
+use std::io;
+#[cfg(windows)]
+use windows::core::PCWSTR;
+#[cfg(windows)]
+use windows::Win32::Foundation::{
+    CloseHandle, ERROR_BROKEN_PIPE, ERROR_PIPE_CONNECTED, HANDLE, INVALID_HANDLE_VALUE,
+};
+#[cfg(windows)]
+use windows::Win32::Storage::FileSystem::{
+    FlushFileBuffers, ReadFile, WriteFile, FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX,
+};
+#[cfg(windows)]
+use windows::Win32::System::Pipes::{
+    ConnectNamedPipe, CreateNamedPipeW, DisconnectNamedPipe, PIPE_READMODE_BYTE, PIPE_TYPE_BYTE,
+    PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+};
+
+// how long a client waits for the app to get to its request
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[cfg(windows)]
+const PIPE_BUFFER_SIZE: u32 = 4096;
+
+// A request read off a connection, waiting on the app to answer it
+pub struct PendingRequest {
+    pub request: Request,
+    reply: Sender<Response>,
+}
+
+impl PendingRequest {
+    pub fn reply(self, response: Response) {
+        if self.reply.send(response).is_err() {
+            debug!("reply | client gave up waiting");
+        }
+    }
+}
+
+// What every connection thread shares with the server
+#[derive(Clone)]
+struct Channels {
+    requests: Sender<PendingRequest>,
+    subscribers: Arc<Mutex<Event<EventMessage>>>,
+}
+
+// Accepts connections on its own thread, one more thread per client. The requests are
+// answered by the app between frames, see `App::handle_ipc`.
+pub struct IpcServer {
+    path: PathBuf,
+    requests: Receiver<PendingRequest>,
+    subscribers: Arc<Mutex<Event<EventMessage>>>,
+}
+
+impl IpcServer {
+    pub fn start(path: PathBuf) -> Result<Self> {
+        let listener = Listener::bind(&path)?;
+        let (sender, requests) = crossbeam_channel::unbounded();
+        let subscribers = Arc::new(Mutex::new(Event::<EventMessage>::new()));
+
+        let channels = Channels {
+            requests: sender,
+            subscribers: subscribers.clone(),
+        };
+        thread::spawn(move || listener.accept(channels));
+        info!("Listening for commands on {}", path.display());
+
+        Ok(Self {
+            path,
+            requests,
+            subscribers,
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn poll(&self) -> Option<PendingRequest> {
+        self.requests.try_recv().ok()
+    }
+
+    // hands `message` to every subscribed connection
+    pub fn broadcast(&self, message: EventMessage) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.broadcast(message);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            debug!("drop | failed to remove {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+// answers every line read from `stream` until the client hangs up or subscribes
+fn serve<S>(stream: S, channels: &Channels)
+where
+    for<'a> &'a S: Read + Write,
+{
+    let reader = BufReader::new(&stream);
+    let mut writer = &stream;
+
+    for line in reader.lines() {
+        let Ok(line) = line else {
+            break;
+        };
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(Request::Subscribe(kinds)) => {
+                subscribe(&mut writer, kinds, channels);
+                break;
+            }
+            Ok(request) => dispatch(request, &channels.requests),
+            Err(e) => Response::Error(format!("Invalid request: {}", e)),
+        };
+
+        if !write_line(&mut writer, &response) {
+            break;
+        }
+    }
+}
+
+// streams the events of `kinds`, or every event when empty, until the client hangs up
+fn subscribe(writer: &mut impl Write, kinds: Vec<EventKind>, channels: &Channels) {
+    let Ok(events) = channels
+        .subscribers
+        .lock()
+        .map(|mut subscribers| subscribers.subscribe())
+    else {
+        write_line(
+            writer,
+            &Response::Error("tile-rs is shutting down".to_string()),
+        );
+        return;
+    };
+
+    if !write_line(writer, &Response::Ok) {
+        return;
+    }
+
+    for message in events {
+        if (kinds.is_empty() || kinds.contains(&message.kind())) && !write_line(writer, &message) {
+            break;
+        }
+    }
+}
+
+// whether `message` was written as a line of its own
+fn write_line(writer: &mut impl Write, message: &(impl Serialize + std::fmt::Debug)) -> bool {
+    let text = match serde_json::to_string(message) {
+        Ok(text) => text + "\n",
+        Err(e) => {
+            error!("write_line | failed to serialize {:?}: {}", message, e);
+            return false;
+        }
+    };
+
+    writer
+        .write_all(text.as_bytes())
+        .and_then(|_| writer.flush())
+        .is_ok()
+}
+
+fn dispatch(request: Request, requests: &Sender<PendingRequest>) -> Response {
+    let (reply, response) = crossbeam_channel::bounded(1);
+
+    if requests.send(PendingRequest { request, reply }).is_err() {
+        return Response::Error("tile-rs is shutting down".to_string());
+    }
+
+    response
+        .recv_timeout(REPLY_TIMEOUT)
+        .unwrap_or_else(|_| Response::Error("tile-rs didn't answer".to_string()))
+}
+
+#[cfg(not(windows))]
+struct Listener(UnixListener);
+
+#[cfg(not(windows))]
+impl Listener {
+    fn bind(path: &Path) -> Result<Self> {
+        // only a socket left behind by an instance that didn't shut down is replaced
+        if let Ok(metadata) = fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                bail!("{} is in the way, it isn't a socket", path.display());
+            }
+            if UnixStream::connect(path).is_ok() {
+                bail!("tile-rs is already running on {}", path.display());
+            }
+
+            fs::remove_file(path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("Failed to listen on {}", path.display()))?;
+
+        Ok(Self(listener))
+    }
+
+    fn accept(self, channels: Channels) {
+        for stream in self.0.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let channels = channels.clone();
+                    thread::spawn(move || serve::<UnixStream>(stream, &channels));
+                }
+                Err(e) => warn!("accept | {}", e),
+            }
+        }
+    }
+}
+
+// One instance of the named pipe, every client connects to an instance of its own
+#[cfg(windows)]
+struct Pipe(HANDLE);
+
+#[cfg(windows)]
+impl Pipe {
+    fn create(name: &[u16], first: bool) -> Result<Self> {
+        // the first instance makes sure no other program owns the name
+        let mut open_mode = PIPE_ACCESS_DUPLEX;
+        if first {
+            open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+        }
+
+        let handle = unsafe {
+            CreateNamedPipeW(
+                PCWSTR(name.as_ptr()),
+                open_mode,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT,
+                PIPE_UNLIMITED_INSTANCES,
+                PIPE_BUFFER_SIZE,
+                PIPE_BUFFER_SIZE,
+                0,
+                None,
+            )
+        };
+
+        if handle == INVALID_HANDLE_VALUE {
+            bail!(windows::core::Error::from_win32());
+        }
+
+        Ok(Self(handle))
+    }
+}
+
+#[cfg(windows)]
+impl Read for &Pipe {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+
+        match unsafe { ReadFile(self.0, Some(buf), Some(&mut read), None) } {
+            Ok(()) => Ok(read as usize),
+            Err(e) if e.code() == ERROR_BROKEN_PIPE.to_hresult() => Ok(0),
+            Err(e) => Err(io::Error::other(e)),
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Write for &Pipe {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut written = 0;
+
+        unsafe { WriteFile(self.0, Some(buf), Some(&mut written), None) }
+            .map(|_| written as usize)
+            .map_err(io::Error::other)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        unsafe { FlushFileBuffers(self.0) }.map_err(io::Error::other)
+    }
+}
+
+#[cfg(windows)]
+impl Drop for Pipe {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = DisconnectNamedPipe(self.0);
+            let _ = CloseHandle(self.0);
+        }
+    }
+}
+
+#[cfg(windows)]
+struct Listener {
+    name: Vec<u16>,
+    pipe: Pipe,
+}
+
+#[cfg(windows)]
+impl Listener {
+    fn bind(path: &Path) -> Result<Self> {
+        let name: Vec<u16> = path
+            .to_string_lossy()
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+
+        let pipe = Pipe::create(&name, true)
+            .with_context(|| format!("Failed to listen on {}", path.display()))?;
+
+        Ok(Self { name, pipe })
+    }
+
+    fn accept(mut self, channels: Channels) {
+        loop {
+            let connected = match unsafe { ConnectNamedPipe(self.pipe.0, None) } {
+                Ok(()) => true,
+                // the client connected before we started waiting
+                Err(e) if e.code() == ERROR_PIPE_CONNECTED.to_hresult() => true,
+                Err(e) => {
+                    warn!("accept | {}", e);
+                    false
+                }
+            };
+
+            let next = match Pipe::create(&self.name, false) {
+                Ok(next) => next,
+                Err(e) => {
+                    error!("accept | {:#}", e);
+                    return;
+                }
+            };
+
+            let pipe = std::mem::replace(&mut self.pipe, next);
+            if connected {
+                let channels = channels.clone();
+                thread::spawn(move || serve::<Pipe>(pipe, &channels));
+            }
+        }
+    }
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::*;
+    use crate::classes::action::Action;
+    use crate::ipc::client::Client;
+    use crate::ipc::protocol::Query;
+
+    #[test]
+    fn answers_each_line_in_order() {
+        let path = std::env::temp_dir().join(format!("tile-rs-{}.sock", std::process::id()));
+        let server = IpcServer::start(path.clone()).unwrap();
+
+        let client_path = path.clone();
+        let client = thread::spawn(move || {
+            let stream = UnixStream::connect(&client_path).unwrap();
+            let mut writer = &stream;
+            writer
+                .write_all(b"{\"command\": {\"SwitchToWorkspace\": 1}}\n\nnonsense\n")
+                .unwrap();
+
+            let mut responses: Vec<Response> = BufReader::new(&stream)
+                .lines()
+                .take(2)
+                .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+                .collect();
+
+            let mut client = Client::connect(&client_path).unwrap();
+            responses.push(client.request(&Request::Query(Query::Monitors)).unwrap());
+            responses
+        });
+
+        let mut answered = Vec::new();
+        while answered.len() < 2 {
+            match server.poll() {
+                Some(pending) => {
+                    answered.push(pending.request.clone());
+                    pending.reply(Response::Monitors(Vec::new()));
+                }
+                None => thread::sleep(Duration::from_millis(1)),
+            }
+        }
+
+        assert_eq!(
+            answered,
+            [
+                Request::Command(Action::SwitchToWorkspace(1)),
+                Request::Query(Query::Monitors),
+            ]
+        );
+
+        let responses = client.join().unwrap();
+        assert_eq!(responses[0], Response::Monitors(Vec::new()));
+        assert!(matches!(&responses[1], Response::Error(e) if e.starts_with("Invalid request")));
+        assert_eq!(responses[2], Response::Monitors(Vec::new()));
+
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn only_stale_sockets_are_replaced() {
+        let path = std::env::temp_dir().join(format!("tile-rs-{}-bind.sock", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        fs::write(&path, "not a socket").unwrap();
+        let error = IpcServer::start(path.clone()).err().unwrap();
+        assert!(error.to_string().ends_with("it isn't a socket"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "not a socket");
+        fs::remove_file(&path).unwrap();
+
+        let server = IpcServer::start(path.clone()).unwrap();
+        let error = IpcServer::start(path.clone()).err().unwrap();
+        assert!(error.to_string().starts_with("tile-rs is already running"));
+        assert!(UnixStream::connect(&path).is_ok());
+
+        // a socket nobody listens on any more
+        drop(server);
+        drop(UnixListener::bind(&path).unwrap());
+        assert!(UnixStream::connect(&path).is_err());
+        let server = IpcServer::start(path.clone()).unwrap();
+        assert!(UnixStream::connect(&path).is_ok());
+
+        drop(server);
+    }
+}
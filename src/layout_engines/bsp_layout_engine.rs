@@ -0,0 +1,417 @@
+use crate::classes::window_location::WindowLocation;
+use crate::classes::window_state::WindowState;
+use crate::csharp::structs::Rectangle;
+use crate::layout_engines::{LayoutEngine, Orientation};
+use crate::window::Window;
+use std::collections::HashMap;
+
+const MIN_RATIO: f64 = 0.1;
+const MAX_RATIO: f64 = 0.9;
+
+#[derive(Debug)]
+enum Node {
+    Leaf(isize),
+    Split {
+        orientation: Orientation,
+        ratio: f64,
+        first: Box<Node>,
+        second: Box<Node>,
+    },
+}
+
+impl Node {
+    fn contains(&self, handle: isize) -> bool {
+        match self {
+            Node::Leaf(leaf) => *leaf == handle,
+            Node::Split { first, second, .. } => first.contains(handle) || second.contains(handle),
+        }
+    }
+
+    fn handles(&self, handles: &mut Vec<isize>) {
+        match self {
+            Node::Leaf(leaf) => handles.push(*leaf),
+            Node::Split { first, second, .. } => {
+                first.handles(handles);
+                second.handles(handles);
+            }
+        }
+    }
+
+    fn layout(&self, rect: Rectangle, rects: &mut HashMap<isize, Rectangle>) {
+        match self {
+            Node::Leaf(leaf) => {
+                rects.insert(*leaf, rect);
+            }
+            Node::Split {
+                orientation,
+                ratio,
+                first,
+                second,
+            } => {
+                let (first_rect, second_rect) = split_rect(rect, *orientation, *ratio);
+                first.layout(first_rect, rects);
+                second.layout(second_rect, rects);
+            }
+        }
+    }
+
+    // replaces the `target` leaf with a split holding `target` and `handle`
+    fn insert(&mut self, target: isize, handle: isize, orientation: Orientation) -> bool {
+        match self {
+            Node::Leaf(leaf) if *leaf == target => {
+                *self = Node::Split {
+                    orientation,
+                    ratio: 0.5,
+                    first: Box::new(Node::Leaf(target)),
+                    second: Box::new(Node::Leaf(handle)),
+                };
+                true
+            }
+            Node::Leaf(_) => false,
+            Node::Split { first, second, .. } => {
+                first.insert(target, handle, orientation)
+                    || second.insert(target, handle, orientation)
+            }
+        }
+    }
+
+    // removes the `handle` leaf, returning `None` when the node itself was that leaf
+    fn remove(self, handle: isize) -> Option<Node> {
+        match self {
+            Node::Leaf(leaf) if leaf == handle => None,
+            Node::Leaf(_) => Some(self),
+            Node::Split {
+                orientation,
+                ratio,
+                first,
+                second,
+            } => match (first.remove(handle), second.remove(handle)) {
+                (Some(first), Some(second)) => Some(Node::Split {
+                    orientation,
+                    ratio,
+                    first: Box::new(first),
+                    second: Box::new(second),
+                }),
+                (Some(remaining), None) | (None, Some(remaining)) => Some(remaining),
+                (None, None) => None,
+            },
+        }
+    }
+
+    // adjusts the split directly above the `handle` leaf so that leaf grows by `delta`
+    fn adjust_ratio(&mut self, handle: isize, delta: f64) -> bool {
+        match self {
+            Node::Leaf(_) => false,
+            Node::Split {
+                ratio,
+                first,
+                second,
+                ..
+            } => {
+                if matches!(**first, Node::Leaf(leaf) if leaf == handle) {
+                    *ratio = (*ratio + delta).clamp(MIN_RATIO, MAX_RATIO);
+                    true
+                } else if matches!(**second, Node::Leaf(leaf) if leaf == handle) {
+                    *ratio = (*ratio - delta).clamp(MIN_RATIO, MAX_RATIO);
+                    true
+                } else {
+                    first.adjust_ratio(handle, delta) || second.adjust_ratio(handle, delta)
+                }
+            }
+        }
+    }
+
+    fn ratio_of(&self, handle: isize) -> Option<f64> {
+        match self {
+            Node::Leaf(_) => None,
+            Node::Split {
+                ratio,
+                first,
+                second,
+                ..
+            } => {
+                if matches!(**first, Node::Leaf(leaf) if leaf == handle) {
+                    Some(*ratio)
+                } else if matches!(**second, Node::Leaf(leaf) if leaf == handle) {
+                    Some(1.0 - *ratio)
+                } else {
+                    first.ratio_of(handle).or_else(|| second.ratio_of(handle))
+                }
+            }
+        }
+    }
+
+    fn reset_ratios(&mut self) {
+        if let Node::Split {
+            ratio,
+            first,
+            second,
+            ..
+        } = self
+        {
+            *ratio = 0.5;
+            first.reset_ratios();
+            second.reset_ratios();
+        }
+    }
+}
+
+fn split_rect(rect: Rectangle, orientation: Orientation, ratio: f64) -> (Rectangle, Rectangle) {
+    match orientation {
+        Orientation::Horizontal => {
+            let width = (rect.width as f64 * ratio).round() as i32;
+            (
+                Rectangle::new(rect.x, rect.y, width, rect.height),
+                Rectangle::new(rect.x + width, rect.y, rect.width - width, rect.height),
+            )
+        }
+        Orientation::Vertical => {
+            let height = (rect.height as f64 * ratio).round() as i32;
+            (
+                Rectangle::new(rect.x, rect.y, rect.width, height),
+                Rectangle::new(rect.x, rect.y + height, rect.width, rect.height - height),
+            )
+        }
+    }
+}
+
+#[allow(dead_code)]
+pub struct BspLayoutEngine {
+    root: Option<Node>,
+    focused: Option<isize>,
+    ratio_increment: f64,
+    name: String,
+}
+
+impl BspLayoutEngine {
+    #[allow(dead_code)]
+    pub fn new() -> BspLayoutEngine {
+        BspLayoutEngine {
+            root: None,
+            focused: None,
+            ratio_increment: 0.03,
+            name: "bsp".to_string(),
+        }
+    }
+
+    // Grows the window by `delta` of the split it shares with its sibling.
+    #[allow(dead_code)]
+    pub fn adjust_ratio(&mut self, handle: isize, delta: f64) -> bool {
+        match &mut self.root {
+            Some(root) => root.adjust_ratio(handle, delta),
+            None => false,
+        }
+    }
+
+    // Share of its split the window currently occupies.
+    #[allow(dead_code)]
+    pub fn ratio_of(&self, handle: isize) -> Option<f64> {
+        self.root.as_ref().and_then(|root| root.ratio_of(handle))
+    }
+
+    fn handles(&self) -> Vec<isize> {
+        let mut handles = Vec::new();
+        if let Some(root) = &self.root {
+            root.handles(&mut handles);
+        }
+        handles
+    }
+
+    fn insert(&mut self, handle: isize, space: Rectangle) {
+        let Some(root) = &mut self.root else {
+            self.root = Some(Node::Leaf(handle));
+            return;
+        };
+
+        let mut rects = HashMap::new();
+        root.layout(space, &mut rects);
+
+        // split the focused leaf, or the last leaf when nothing is focused
+        let target = self
+            .focused
+            .filter(|focused| root.contains(*focused))
+            .unwrap_or_else(|| {
+                let mut handles = Vec::new();
+                root.handles(&mut handles);
+                *handles.last().unwrap()
+            });
+
+        let orientation = match rects.get(&target) {
+            Some(rect) if rect.height > rect.width => Orientation::Vertical,
+            _ => Orientation::Horizontal,
+        };
+
+        root.insert(target, handle, orientation);
+    }
+
+    fn remove(&mut self, handle: isize) {
+        self.root = self.root.take().and_then(|root| root.remove(handle));
+    }
+}
+
+impl LayoutEngine for BspLayoutEngine {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn calc_layout(
+        &mut self,
+        windows: &[&Window],
+        space_width: i32,
+        space_height: i32,
+    ) -> Vec<WindowLocation> {
+        let space = Rectangle::new(0, 0, space_width, space_height);
+
+        for handle in self.handles() {
+            if !windows.iter().any(|w| w.handle == handle) {
+                self.remove(handle);
+            }
+        }
+
+        let known = self.handles();
+
+        // new windows split the one focused now, focus may have moved since the last layout
+        if let Some(window) = windows
+            .iter()
+            .find(|w| known.contains(&w.handle) && w.is_focused())
+        {
+            self.focused = Some(window.handle);
+        }
+
+        for window in windows.iter().filter(|w| !known.contains(&w.handle)) {
+            self.insert(window.handle, space);
+        }
+
+        if let Some(window) = windows.iter().find(|w| w.is_focused()) {
+            self.focused = Some(window.handle);
+        }
+
+        let mut rects = HashMap::new();
+        if let Some(root) = &self.root {
+            root.layout(space, &mut rects);
+        }
+
+        windows
+            .iter()
+            .map(|window| {
+                let rect = rects[&window.handle];
+                WindowLocation::new(rect.x, rect.y, rect.width, rect.height, WindowState::Normal)
+            })
+            .collect()
+    }
+
+    fn shrink_primary_area(&mut self) {
+        if let Some(focused) = self.focused {
+            self.adjust_ratio(focused, -self.ratio_increment);
+        }
+    }
+
+    fn expand_primary_area(&mut self) {
+        if let Some(focused) = self.focused {
+            self.adjust_ratio(focused, self.ratio_increment);
+        }
+    }
+
+    fn reset_primary_area(&mut self) {
+        if let Some(root) = &mut self.root {
+            root.reset_ratios();
+        }
+    }
+
+    fn increment_num_in_primary(&mut self) {}
+    fn decrement_num_in_primary(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout_engines::{rects, test_windows};
+
+    #[test]
+    fn splits_focused_leaf() {
+        let windows = test_windows(3);
+        let mut engine = BspLayoutEngine::new();
+
+        windows[0].focus();
+        engine.calc_layout(&[&windows[0], &windows[1]], 1000, 800);
+
+        let layout = engine.calc_layout(&windows.iter().collect::<Vec<_>>(), 1000, 800);
+
+        assert_eq!(
+            rects(&layout),
+            [(0, 0, 500, 400), (500, 0, 500, 800), (0, 400, 500, 400)]
+        );
+    }
+
+    #[test]
+    fn splits_leaf_focused_since_last_layout() {
+        let windows = test_windows(3);
+        let mut engine = BspLayoutEngine::new();
+
+        windows[0].focus();
+        engine.calc_layout(&[&windows[0], &windows[1]], 1000, 800);
+
+        windows[1].focus();
+        let layout = engine.calc_layout(&windows.iter().collect::<Vec<_>>(), 1000, 800);
+
+        assert_eq!(
+            rects(&layout),
+            [(0, 0, 500, 800), (500, 0, 500, 400), (500, 400, 500, 400)]
+        );
+    }
+
+    #[test]
+    fn removing_leaf_collapses_split() {
+        let windows = test_windows(3);
+        let mut engine = BspLayoutEngine::new();
+
+        engine.calc_layout(&windows.iter().collect::<Vec<_>>(), 1000, 800);
+        let layout = engine.calc_layout(&[&windows[0], &windows[2]], 1000, 800);
+
+        assert_eq!(rects(&layout), [(0, 0, 500, 800), (500, 0, 500, 800)]);
+
+        let layout = engine.calc_layout(&[&windows[2]], 1000, 800);
+
+        assert_eq!(rects(&layout), [(0, 0, 1000, 800)]);
+    }
+
+    #[test]
+    fn adjusted_ratios_survive_relayout() {
+        let windows = test_windows(3);
+        let mut engine = BspLayoutEngine::new();
+        let all = windows.iter().collect::<Vec<_>>();
+
+        engine.calc_layout(&all, 1000, 800);
+        assert!(engine.adjust_ratio(windows[0].handle, 0.2));
+
+        let layout = engine.calc_layout(&all, 1000, 800);
+        assert_eq!(layout[0].width, 700);
+        assert_eq!(engine.ratio_of(windows[1].handle), Some(0.5));
+
+        let layout = engine.calc_layout(&all, 1000, 800);
+        assert_eq!(
+            rects(&layout),
+            [(0, 0, 700, 800), (700, 0, 300, 400), (700, 400, 300, 400)]
+        );
+
+        engine.reset_primary_area();
+
+        let layout = engine.calc_layout(&all, 1000, 800);
+        assert_eq!(layout[0].width, 500);
+    }
+
+    #[test]
+    fn expand_primary_area_grows_focused_window() {
+        let windows = test_windows(2);
+        let mut engine = BspLayoutEngine::new();
+        let all = windows.iter().collect::<Vec<_>>();
+
+        windows[1].focus();
+        engine.calc_layout(&all, 1000, 800);
+        engine.expand_primary_area();
+
+        let layout = engine.calc_layout(&all, 1000, 800);
+
+        assert_eq!(rects(&layout), [(0, 0, 470, 800), (470, 0, 530, 800)]);
+    }
+}
@@ -1,13 +1,9 @@
 use crate::classes::window_location::WindowLocation;
 use crate::classes::window_state::WindowState;
-use crate::layout_engines::LayoutEngine;
+use crate::layout_engines::{LayoutEngine, Orientation};
 use crate::window::Window;
 
-pub enum Orientation {
-    Horizontal,
-    Vertical,
-}
-
+#[allow(dead_code)]
 pub struct DwindleLayoutEngine {
     num_in_primary: i32,
     primary_percent: f64,
@@ -33,6 +29,10 @@ impl DwindleLayoutEngine {
     pub fn get_num_in_primary(&self) -> i32 {
         self.num_in_primary + self.num_in_primary_offset
     }
+
+    fn get_primary_percent(&self) -> f64 {
+        (self.primary_percent + self.primary_percent_offset).clamp(0.1, 0.9)
+    }
 }
 
 impl LayoutEngine for DwindleLayoutEngine {
@@ -54,8 +54,7 @@ impl LayoutEngine for DwindleLayoutEngine {
         }
 
         let num_in_primary = std::cmp::min(self.get_num_in_primary(), num_windows);
-        let primary_width =
-            (space_width as f64 * (self.primary_percent + self.primary_percent_offset)) as i32;
+        let primary_width = (space_width as f64 * self.get_primary_percent()) as i32;
         let primary_height = space_height / num_in_primary;
         let _height = space_height / std::cmp::max(num_windows - num_in_primary, 1);
 
@@ -137,4 +136,15 @@ impl LayoutEngine for DwindleLayoutEngine {
             self.num_in_primary_offset -= 1;
         }
     }
+
+    fn primary(&self) -> Option<(i32, f64)> {
+        Some((self.get_num_in_primary(), self.get_primary_percent()))
+    }
+
+    fn set_primary(&mut self, num_in_primary: i32, primary_percent: f64) {
+        self.num_in_primary = num_in_primary.max(1);
+        self.primary_percent = primary_percent.clamp(0.1, 0.9);
+        self.num_in_primary_offset = 0;
+        self.primary_percent_offset = 0.0;
+    }
 }
@@ -3,6 +3,7 @@ use crate::classes::window_state::WindowState;
 use crate::layout_engines::LayoutEngine;
 use crate::window::Window;
 
+#[allow(dead_code)]
 pub struct FocusLayoutEngine {
     num_in_primary: i32,
     primary_percent: f64,
@@ -29,6 +30,10 @@ impl FocusLayoutEngine {
         self.num_in_primary + self.num_in_primary_offset
     }
 
+    fn get_primary_percent(&self) -> f64 {
+        (self.primary_percent + self.primary_percent_offset).clamp(0.1, 0.9)
+    }
+
     fn get_nb_left_windows(&self, num_windows: i32, num_in_primary: i32) -> i32 {
         (num_windows - num_in_primary + 1) / 2
     }
@@ -64,8 +69,7 @@ impl LayoutEngine for FocusLayoutEngine {
         let nb_left_windows = self.get_nb_left_windows(num_windows, num_in_primary);
         let nb_right_windows = self.get_nb_right_windows(num_windows, num_in_primary);
 
-        let mut primary_width =
-            (space_width as f64 * (self.primary_percent + self.primary_percent_offset)) as i32;
+        let mut primary_width = (space_width as f64 * self.get_primary_percent()) as i32;
         let mut secondary_width = (space_width - primary_width) / 2;
 
         let primary_height = space_height / num_in_primary;
@@ -132,4 +136,15 @@ impl LayoutEngine for FocusLayoutEngine {
             self.num_in_primary_offset -= 1;
         }
     }
+
+    fn primary(&self) -> Option<(i32, f64)> {
+        Some((self.get_num_in_primary(), self.get_primary_percent()))
+    }
+
+    fn set_primary(&mut self, num_in_primary: i32, primary_percent: f64) {
+        self.num_in_primary = num_in_primary.max(1);
+        self.primary_percent = primary_percent.clamp(0.1, 0.9);
+        self.num_in_primary_offset = 0;
+        self.primary_percent_offset = 0.0;
+    }
 }
@@ -3,6 +3,7 @@ use crate::classes::window_state::WindowState;
 use crate::layout_engines::LayoutEngine;
 use crate::window::Window;
 
+#[allow(dead_code)]
 pub struct FullLayoutEngine {
     last_full: Option<isize>,
     name: String,
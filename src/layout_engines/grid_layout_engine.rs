@@ -2,18 +2,45 @@ use crate::classes::window_location::WindowLocation;
 use crate::classes::window_state::WindowState;
 use crate::layout_engines::LayoutEngine;
 use crate::window::Window;
+use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum FillOrder {
+    #[default]
+    RowMajor,
+    ColumnMajor,
+}
+
+impl FillOrder {
+    pub fn variants() -> [FillOrder; 2] {
+        [FillOrder::RowMajor, FillOrder::ColumnMajor]
+    }
+}
+
+#[allow(dead_code)]
 pub struct GridLayoutEngine {
+    fill_order: FillOrder,
+    columns: usize,
     name: String,
 }
 
 impl GridLayoutEngine {
+    // `columns` of 0 picks the most square grid for the number of windows
     #[allow(dead_code)]
-    pub fn new() -> Self {
+    pub fn new(fill_order: FillOrder, columns: usize) -> Self {
         Self {
-            name: "full".to_string(),
+            fill_order,
+            columns,
+            name: "grid".to_string(),
         }
     }
+
+    // start and size of cell `index` out of `count` evenly sized cells
+    fn cell(space: i32, index: i32, count: i32) -> (i32, i32) {
+        let start = space * index / count;
+        let end = space * (index + 1) / count;
+        (start, end - start)
+    }
 }
 
 impl LayoutEngine for GridLayoutEngine {
@@ -28,31 +55,60 @@ impl LayoutEngine for GridLayoutEngine {
         space_height: i32,
     ) -> Vec<WindowLocation> {
         let mut list = Vec::new();
-        let num_windows = windows.len();
+        let num_windows = windows.len() as i32;
 
         if num_windows == 0 {
             return list;
         }
 
-        let grid_width = (num_windows as f64).sqrt().ceil() as i32;
-        let grid_height = (num_windows as f64 / grid_width as f64).ceil() as i32;
+        let columns = if self.columns == 0 {
+            (num_windows as f64).sqrt().ceil() as i32
+        } else {
+            std::cmp::min(self.columns as i32, num_windows)
+        };
+        let rows = (num_windows + columns - 1) / columns;
+
+        // windows in each column when filling column by column, a fixed column count
+        // spreads them over every column with the first ones taking the extra windows
+        let column_sizes: Vec<i32> = if self.columns == 0 {
+            (0..(num_windows + rows - 1) / rows)
+                .map(|column| std::cmp::min(rows, num_windows - column * rows))
+                .collect()
+        } else {
+            (0..columns)
+                .map(|column| num_windows / columns + i32::from(column < num_windows % columns))
+                .collect()
+        };
 
-        let width = space_width / grid_width;
-        let height = space_height / grid_height;
+        for i in 0..num_windows {
+            let location = match self.fill_order {
+                FillOrder::RowMajor => {
+                    let row = i / columns;
+                    let column = i % columns;
+                    // the last row stretches its cells across the full width
+                    let in_row = std::cmp::min(columns, num_windows - row * columns);
 
-        for i in 0..windows.len() {
-            let i = i as i32;
+                    let (x, width) = Self::cell(space_width, column, in_row);
+                    let (y, height) = Self::cell(space_height, row, rows);
+                    WindowLocation::new(x, y, width, height, WindowState::Normal)
+                }
+                FillOrder::ColumnMajor => {
+                    let (mut column, mut row) = (0, i);
+                    while row >= column_sizes[column] {
+                        row -= column_sizes[column];
+                        column += 1;
+                    }
+                    // shorter columns stretch their cells across the full height
+                    let in_column = column_sizes[column];
 
-            let x = i / grid_width * width;
-            let y = i % grid_height * height;
+                    let (x, width) =
+                        Self::cell(space_width, column as i32, column_sizes.len() as i32);
+                    let (y, height) = Self::cell(space_height, row, in_column);
+                    WindowLocation::new(x, y, width, height, WindowState::Normal)
+                }
+            };
 
-            list.push(WindowLocation::new(
-                x,
-                y,
-                width,
-                height,
-                WindowState::Normal,
-            ));
+            list.push(location);
         }
 
         list
@@ -64,3 +120,98 @@ impl LayoutEngine for GridLayoutEngine {
     fn increment_num_in_primary(&mut self) {}
     fn decrement_num_in_primary(&mut self) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout_engines::{rects, test_windows};
+
+    fn layout(engine: &mut GridLayoutEngine, count: usize) -> Vec<(i32, i32, i32, i32)> {
+        let windows = test_windows(count);
+        rects(&engine.calc_layout(&windows.iter().collect::<Vec<_>>(), 1200, 900))
+    }
+
+    fn assert_no_overlap(rects: &[(i32, i32, i32, i32)]) {
+        for (i, a) in rects.iter().enumerate() {
+            for b in rects.iter().skip(i + 1) {
+                let overlaps =
+                    a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3;
+                assert!(!overlaps, "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn row_major_stretches_last_row() {
+        let mut engine = GridLayoutEngine::new(FillOrder::RowMajor, 0);
+
+        assert_eq!(
+            layout(&mut engine, 5),
+            [
+                (0, 0, 400, 450),
+                (400, 0, 400, 450),
+                (800, 0, 400, 450),
+                (0, 450, 600, 450),
+                (600, 450, 600, 450),
+            ]
+        );
+    }
+
+    #[test]
+    fn column_major_stretches_last_column() {
+        let mut engine = GridLayoutEngine::new(FillOrder::ColumnMajor, 0);
+
+        assert_eq!(
+            layout(&mut engine, 5),
+            [
+                (0, 0, 400, 450),
+                (0, 450, 400, 450),
+                (400, 0, 400, 450),
+                (400, 450, 400, 450),
+                (800, 0, 400, 900),
+            ]
+        );
+    }
+
+    #[test]
+    fn fixed_column_count() {
+        let mut engine = GridLayoutEngine::new(FillOrder::RowMajor, 2);
+
+        assert_eq!(
+            layout(&mut engine, 3),
+            [(0, 0, 600, 450), (600, 0, 600, 450), (0, 450, 1200, 450)]
+        );
+    }
+
+    #[test]
+    fn column_major_fixed_column_count() {
+        let mut engine = GridLayoutEngine::new(FillOrder::ColumnMajor, 3);
+
+        assert_eq!(
+            layout(&mut engine, 4),
+            [
+                (0, 0, 400, 450),
+                (0, 450, 400, 450),
+                (400, 0, 400, 900),
+                (800, 0, 400, 900),
+            ]
+        );
+    }
+
+    #[test]
+    fn never_overlaps_and_fills_space() {
+        for fill_order in FillOrder::variants() {
+            for columns in 0..4 {
+                let mut engine = GridLayoutEngine::new(fill_order, columns);
+
+                for count in 1..=12 {
+                    let rects = layout(&mut engine, count);
+                    assert_no_overlap(&rects);
+
+                    let area: i32 = rects.iter().map(|r| r.2 * r.3).sum();
+                    assert_eq!(area, 1200 * 900, "{:?} {} {}", fill_order, columns, count);
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,10 @@
 use crate::classes::window_location::WindowLocation;
+use crate::csharp::structs::Rectangle;
 use crate::window::Window;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
+pub mod bsp_layout_engine;
 pub mod dwindle_layout_engine;
 pub mod focus_layout_engine;
 pub mod full_layout_engine;
@@ -12,50 +14,70 @@ pub mod tall_layout_engine;
 
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum LayoutEngineType {
+    Bsp,
     Dwindle,
     Focus,
     #[default]
     Full,
     Grid,
-    // Panel,
-    // Tall,
+    Panel,
+    Tall,
 }
 
 impl LayoutEngineType {
-    pub fn variants() -> [LayoutEngineType; 4] {
+    pub fn variants() -> [LayoutEngineType; 7] {
         [
+            LayoutEngineType::Bsp,
             LayoutEngineType::Dwindle,
             LayoutEngineType::Focus,
             LayoutEngineType::Full,
             LayoutEngineType::Grid,
-            // LayoutEngineType::Panel,
-            // LayoutEngineType::Tall,
+            LayoutEngineType::Panel,
+            LayoutEngineType::Tall,
         ]
     }
 
+    #[allow(dead_code)]
     pub fn previous(&self) -> Self {
         match self {
-            LayoutEngineType::Dwindle => LayoutEngineType::Grid,
+            LayoutEngineType::Bsp => LayoutEngineType::Tall,
+            LayoutEngineType::Dwindle => LayoutEngineType::Bsp,
             LayoutEngineType::Focus => LayoutEngineType::Dwindle,
             LayoutEngineType::Full => LayoutEngineType::Focus,
             LayoutEngineType::Grid => LayoutEngineType::Full,
-            // LayoutEngineType::Panel => LayoutEngineType::Grid,
-            // LayoutEngineType::Tall => LayoutEngineType::Panel,
+            LayoutEngineType::Panel => LayoutEngineType::Grid,
+            LayoutEngineType::Tall => LayoutEngineType::Panel,
         }
     }
 
+    #[allow(dead_code)]
     pub fn next(&self) -> Self {
         match self {
+            LayoutEngineType::Bsp => LayoutEngineType::Dwindle,
             LayoutEngineType::Dwindle => LayoutEngineType::Focus,
             LayoutEngineType::Focus => LayoutEngineType::Full,
             LayoutEngineType::Full => LayoutEngineType::Grid,
-            LayoutEngineType::Grid => LayoutEngineType::Dwindle,
-            // LayoutEngineType::Panel => LayoutEngineType::Tall,
-            // LayoutEngineType::Tall => LayoutEngineType::Dwindle,
+            LayoutEngineType::Grid => LayoutEngineType::Panel,
+            LayoutEngineType::Panel => LayoutEngineType::Tall,
+            LayoutEngineType::Tall => LayoutEngineType::Bsp,
         }
     }
 }
 
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Orientation {
+    #[default]
+    Horizontal,
+    Vertical,
+}
+
+impl Orientation {
+    pub fn variants() -> [Orientation; 2] {
+        [Orientation::Horizontal, Orientation::Vertical]
+    }
+}
+
+#[allow(dead_code)]
 pub trait LayoutEngine {
     // the name of the layout engine
     fn name(&self) -> &str;
@@ -68,6 +90,14 @@ pub trait LayoutEngine {
         space_height: i32,
     ) -> Vec<WindowLocation>;
 
+    // calculate the desired layout inside `area`, offset to the area's origin
+    fn calc_layout_in(&mut self, windows: &[&Window], area: Rectangle) -> Vec<WindowLocation> {
+        let mut list = self.calc_layout(windows, area.width, area.height);
+        list.iter_mut()
+            .for_each(|location| location.offset(area.x, area.y));
+        list
+    }
+
     // shrink the primary area of the layout engine
     fn shrink_primary_area(&mut self);
 
@@ -82,6 +112,14 @@ pub trait LayoutEngine {
 
     // decrement the number of windows in the layout's primary area
     fn decrement_num_in_primary(&mut self);
+
+    // the number of windows in and share of the primary area, if the layout has one
+    fn primary(&self) -> Option<(i32, f64)> {
+        None
+    }
+
+    // replace the primary area, dropping any earlier shrink, expand or count changes
+    fn set_primary(&mut self, _num_in_primary: i32, _primary_percent: f64) {}
 }
 
 impl Debug for dyn LayoutEngine {
@@ -89,3 +127,35 @@ impl Debug for dyn LayoutEngine {
         f.debug_struct("LayoutEngine").finish()
     }
 }
+
+#[cfg(test)]
+pub fn test_windows(count: usize) -> Vec<Window> {
+    use crate::backends::fake_backend::{FakeBackend, FakeWindow};
+    use crate::backends::WindowBackend;
+    use crate::csharp::structs::Rectangle;
+    use std::sync::Arc;
+
+    let backend = Arc::new(FakeBackend::new());
+
+    (0..count)
+        .map(|i| {
+            let handle = backend.add_window(FakeWindow::new(
+                &format!("Window {}", i),
+                "Notepad",
+                "notepad.exe",
+                Rectangle::new(0, 0, 800, 600),
+            ));
+            let backend: Arc<dyn WindowBackend> = backend.clone();
+            Window::new(handle, backend).unwrap()
+        })
+        .collect()
+}
+
+// the position and size of each location, to compare layouts against
+#[cfg(test)]
+pub fn rects(locations: &[WindowLocation]) -> Vec<(i32, i32, i32, i32)> {
+    locations
+        .iter()
+        .map(|l| (l.x, l.y, l.width, l.height))
+        .collect()
+}
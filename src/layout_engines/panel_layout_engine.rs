@@ -1 +1,225 @@
-// TODO
+use crate::classes::window_location::WindowLocation;
+use crate::classes::window_state::WindowState;
+use crate::layout_engines::{LayoutEngine, Orientation};
+use crate::window::Window;
+
+#[allow(dead_code)]
+pub struct PanelLayoutEngine {
+    orientation: Orientation,
+    primary_percent_increment: f64,
+    primary_percent_offset: f64,
+    num_windows: i32, // as of the last layout, the primary percent is relative to it
+    name: String,
+}
+
+impl PanelLayoutEngine {
+    #[allow(dead_code)]
+    pub fn new(orientation: Orientation) -> PanelLayoutEngine {
+        PanelLayoutEngine {
+            orientation,
+            primary_percent_increment: 0.03,
+            primary_percent_offset: 0.0,
+            num_windows: 2,
+            name: "panel".to_string(),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+    }
+
+    // the equal split, and the range the primary percent stays in around it. The floor gives
+    // way to the equal split once there are more than ten windows.
+    fn get_primary_bounds(num_windows: i32) -> (f64, f64, f64) {
+        let equal = 1.0 / num_windows.max(2) as f64;
+        (equal, equal.min(0.1), 0.9)
+    }
+
+    fn get_primary_percent(&self, num_windows: i32) -> f64 {
+        let (equal, min, max) = Self::get_primary_bounds(num_windows);
+        (equal + self.primary_percent_offset).clamp(min, max)
+    }
+
+    // keeps the stored offset in range, so shrinking right after expanding to the limit shows
+    fn set_primary_percent_offset(&mut self, offset: f64) {
+        let (equal, min, max) = Self::get_primary_bounds(self.num_windows);
+        self.primary_percent_offset = offset.clamp(min - equal, max - equal);
+    }
+}
+
+impl LayoutEngine for PanelLayoutEngine {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn calc_layout(
+        &mut self,
+        windows: &[&Window],
+        space_width: i32,
+        space_height: i32,
+    ) -> Vec<WindowLocation> {
+        let mut list = Vec::new();
+        let num_windows = windows.len() as i32;
+
+        if num_windows == 0 {
+            return list;
+        }
+
+        if num_windows > 1 {
+            self.num_windows = num_windows;
+        }
+
+        let space = match self.orientation {
+            Orientation::Horizontal => space_width,
+            Orientation::Vertical => space_height,
+        };
+
+        // the primary panel takes its weighted share, the rest split what is left evenly
+        let (primary_size, secondary_size) = if num_windows == 1 {
+            (space, 0)
+        } else {
+            let primary_size =
+                (space as f64 * self.get_primary_percent(num_windows)).round() as i32;
+            (primary_size, (space - primary_size) / (num_windows - 1))
+        };
+
+        let mut offset = 0;
+
+        for i in 0..num_windows {
+            // the last panel absorbs the rounding remainder
+            let size = if i == 0 {
+                primary_size
+            } else if i == num_windows - 1 {
+                space - offset
+            } else {
+                secondary_size
+            };
+
+            list.push(match self.orientation {
+                Orientation::Horizontal => {
+                    WindowLocation::new(offset, 0, size, space_height, WindowState::Normal)
+                }
+                Orientation::Vertical => {
+                    WindowLocation::new(0, offset, space_width, size, WindowState::Normal)
+                }
+            });
+
+            offset += size;
+        }
+
+        list
+    }
+
+    fn shrink_primary_area(&mut self) {
+        self.set_primary_percent_offset(
+            self.primary_percent_offset - self.primary_percent_increment,
+        );
+    }
+
+    fn expand_primary_area(&mut self) {
+        self.set_primary_percent_offset(
+            self.primary_percent_offset + self.primary_percent_increment,
+        );
+    }
+
+    fn reset_primary_area(&mut self) {
+        self.primary_percent_offset = 0.0;
+    }
+
+    fn increment_num_in_primary(&mut self) {}
+    fn decrement_num_in_primary(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout_engines::{rects, test_windows};
+
+    #[test]
+    fn horizontal_panels_are_equal_columns() {
+        let windows = test_windows(3);
+        let mut engine = PanelLayoutEngine::new(Orientation::Horizontal);
+
+        let layout = engine.calc_layout(&windows.iter().collect::<Vec<_>>(), 1000, 800);
+
+        assert_eq!(
+            rects(&layout),
+            [(0, 0, 333, 800), (333, 0, 333, 800), (666, 0, 334, 800)]
+        );
+    }
+
+    #[test]
+    fn vertical_panels_are_equal_rows() {
+        let windows = test_windows(4);
+        let mut engine = PanelLayoutEngine::new(Orientation::Vertical);
+
+        let layout = engine.calc_layout(&windows.iter().collect::<Vec<_>>(), 1000, 800);
+
+        assert_eq!(
+            rects(&layout),
+            [
+                (0, 0, 1000, 200),
+                (0, 200, 1000, 200),
+                (0, 400, 1000, 200),
+                (0, 600, 1000, 200),
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_primary_area_weights_first_panel() {
+        let windows = test_windows(3);
+        let mut engine = PanelLayoutEngine::new(Orientation::Horizontal);
+
+        for _ in 0..5 {
+            engine.expand_primary_area();
+        }
+
+        let layout = engine.calc_layout(&windows.iter().collect::<Vec<_>>(), 1200, 800);
+
+        assert_eq!(
+            rects(&layout),
+            [(0, 0, 580, 800), (580, 0, 310, 800), (890, 0, 310, 800)]
+        );
+
+        engine.reset_primary_area();
+
+        let layout = engine.calc_layout(&windows.iter().collect::<Vec<_>>(), 1200, 800);
+
+        assert_eq!(layout[0].width, 400);
+    }
+
+    #[test]
+    fn shrink_undoes_expand_at_the_limit() {
+        let windows = test_windows(2);
+        let windows: Vec<_> = windows.iter().collect();
+        let mut engine = PanelLayoutEngine::new(Orientation::Horizontal);
+        engine.calc_layout(&windows, 1000, 800);
+
+        for _ in 0..50 {
+            engine.expand_primary_area();
+        }
+        assert_eq!(engine.calc_layout(&windows, 1000, 800)[0].width, 900);
+
+        engine.shrink_primary_area();
+        assert_eq!(engine.calc_layout(&windows, 1000, 800)[0].width, 870);
+    }
+
+    #[test]
+    fn many_windows_split_equally() {
+        let windows = test_windows(20);
+        let windows: Vec<_> = windows.iter().collect();
+        let mut engine = PanelLayoutEngine::new(Orientation::Horizontal);
+
+        let layout = engine.calc_layout(&windows, 2000, 800);
+        assert!(layout.iter().all(|l| l.width == 100));
+
+        // already at the floor, and expanding right away shows
+        engine.shrink_primary_area();
+        assert_eq!(engine.calc_layout(&windows, 2000, 800)[0].width, 100);
+
+        engine.expand_primary_area();
+        assert_eq!(engine.calc_layout(&windows, 2000, 800)[0].width, 160);
+    }
+}
@@ -1 +1,188 @@
-// TODO
\ No newline at end of file
+use crate::classes::window_location::WindowLocation;
+use crate::classes::window_state::WindowState;
+use crate::layout_engines::LayoutEngine;
+use crate::window::Window;
+
+#[allow(dead_code)]
+pub struct TallLayoutEngine {
+    num_in_primary: i32,
+    primary_percent: f64,
+    primary_percent_increment: f64,
+    num_in_primary_offset: i32,
+    primary_percent_offset: f64,
+    name: String,
+}
+
+impl TallLayoutEngine {
+    #[allow(dead_code)]
+    pub fn new() -> TallLayoutEngine {
+        TallLayoutEngine {
+            num_in_primary: 1,
+            primary_percent: 0.5,
+            primary_percent_increment: 0.03,
+            num_in_primary_offset: 0,
+            primary_percent_offset: 0.0,
+            name: "tall".to_string(),
+        }
+    }
+
+    fn get_num_in_primary(&self) -> i32 {
+        self.num_in_primary + self.num_in_primary_offset
+    }
+
+    fn get_primary_percent(&self) -> f64 {
+        (self.primary_percent + self.primary_percent_offset).clamp(0.1, 0.9)
+    }
+}
+
+impl LayoutEngine for TallLayoutEngine {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn calc_layout(
+        &mut self,
+        windows: &[&Window],
+        space_width: i32,
+        space_height: i32,
+    ) -> Vec<WindowLocation> {
+        let mut list = Vec::new();
+        let num_windows = windows.len() as i32;
+
+        if num_windows == 0 {
+            return list;
+        }
+
+        let num_in_primary = std::cmp::min(self.get_num_in_primary(), num_windows);
+
+        // a primary column without a secondary column spans the whole space
+        let primary_width = if num_in_primary >= num_windows {
+            space_width
+        } else {
+            (space_width as f64 * self.get_primary_percent()) as i32
+        };
+
+        let secondary_width = space_width - primary_width;
+
+        let primary_height = space_height / num_in_primary;
+        let secondary_height = space_height / std::cmp::max(num_windows - num_in_primary, 1);
+
+        for i in 0..num_windows {
+            if i < num_in_primary {
+                list.push(WindowLocation::new(
+                    0,
+                    i * primary_height,
+                    primary_width,
+                    primary_height,
+                    WindowState::Normal,
+                ));
+            } else {
+                list.push(WindowLocation::new(
+                    primary_width,
+                    (i - num_in_primary) * secondary_height,
+                    secondary_width,
+                    secondary_height,
+                    WindowState::Normal,
+                ));
+            }
+        }
+
+        list
+    }
+
+    fn shrink_primary_area(&mut self) {
+        if self.get_primary_percent() - self.primary_percent_increment >= 0.1 {
+            self.primary_percent_offset -= self.primary_percent_increment;
+        }
+    }
+
+    fn expand_primary_area(&mut self) {
+        if self.get_primary_percent() + self.primary_percent_increment <= 0.9 {
+            self.primary_percent_offset += self.primary_percent_increment;
+        }
+    }
+
+    fn reset_primary_area(&mut self) {
+        self.primary_percent_offset = 0.0;
+    }
+
+    fn increment_num_in_primary(&mut self) {
+        self.num_in_primary_offset += 1;
+    }
+
+    fn decrement_num_in_primary(&mut self) {
+        if self.get_num_in_primary() > 1 {
+            self.num_in_primary_offset -= 1;
+        }
+    }
+
+    fn primary(&self) -> Option<(i32, f64)> {
+        Some((self.get_num_in_primary(), self.get_primary_percent()))
+    }
+
+    fn set_primary(&mut self, num_in_primary: i32, primary_percent: f64) {
+        self.num_in_primary = num_in_primary.max(1);
+        self.primary_percent = primary_percent.clamp(0.1, 0.9);
+        self.num_in_primary_offset = 0;
+        self.primary_percent_offset = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout_engines::{rects, test_windows};
+
+    #[test]
+    fn single_window_spans_space() {
+        let windows = test_windows(1);
+        let mut engine = TallLayoutEngine::new();
+
+        let layout = engine.calc_layout(&windows.iter().collect::<Vec<_>>(), 1000, 800);
+
+        assert_eq!(rects(&layout), [(0, 0, 1000, 800)]);
+    }
+
+    #[test]
+    fn stacks_secondary_windows_on_the_right() {
+        let windows = test_windows(3);
+        let mut engine = TallLayoutEngine::new();
+
+        let layout = engine.calc_layout(&windows.iter().collect::<Vec<_>>(), 1000, 800);
+
+        assert_eq!(
+            rects(&layout),
+            [(0, 0, 500, 800), (500, 0, 500, 400), (500, 400, 500, 400)]
+        );
+    }
+
+    #[test]
+    fn honors_primary_count_and_percent() {
+        let windows = test_windows(4);
+        let mut engine = TallLayoutEngine::new();
+        engine.increment_num_in_primary();
+        engine.expand_primary_area();
+        engine.expand_primary_area();
+
+        let layout = engine.calc_layout(&windows.iter().collect::<Vec<_>>(), 1000, 800);
+
+        assert_eq!(
+            rects(&layout),
+            [
+                (0, 0, 560, 400),
+                (0, 400, 560, 400),
+                (560, 0, 440, 400),
+                (560, 400, 440, 400),
+            ]
+        );
+
+        engine.reset_primary_area();
+        engine.decrement_num_in_primary();
+        engine.decrement_num_in_primary();
+
+        let layout = engine.calc_layout(&windows.iter().collect::<Vec<_>>(), 1000, 800);
+
+        assert_eq!(layout[0].width, 500);
+        assert_eq!(layout[0].height, 800);
+    }
+}
@@ -2,12 +2,16 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app;
+mod backends;
 mod classes;
+mod config;
 mod context;
 mod csharp;
 mod delegates;
 mod helpers;
+mod ipc;
 mod layout_engines;
+mod snapshot;
 mod window;
 mod windows_manager;
 mod workspace;
@@ -0,0 +1,193 @@
+use crate::classes::gaps::Gaps;
+use crate::classes::monitor::Monitor;
+use crate::classes::window_location::WindowLocation;
+use crate::classes::workspace_layout::WorkspaceLayout;
+use crate::csharp::structs::Rectangle;
+use crate::window::Window;
+use crate::windows_manager::WindowsManager;
+use crate::workspace::Workspace;
+use crate::workspace_manager::WorkspaceManager;
+use serde::{Deserialize, Serialize};
+
+pub const SNAPSHOT_FILE_NAME: &str = "tile-rs-state.json";
+
+// Everything the managers know at one point in time. Monitors, workspaces and their
+// windows keep their order, so two snapshots can be diffed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub focused_monitor: usize,
+    pub focused_window: Option<isize>,
+    pub monitors: Vec<MonitorSnapshot>,
+    pub workspaces: Vec<WorkspaceSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitorSnapshot {
+    pub index: usize,
+    pub name: String,
+    pub primary: bool,
+    pub bounds: Rectangle,
+    pub working_area: Rectangle,
+    pub workspace: Option<String>, // the one shown
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceSnapshot {
+    pub index: usize,
+    pub name: String,
+    pub monitor: Option<usize>,
+    pub area: Option<Rectangle>,
+    pub layout: WorkspaceLayout,
+    pub gaps: Gaps,
+    pub windows: Vec<WindowSnapshot>, // in layout order
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowSnapshot {
+    pub handle: isize,
+    pub title: String,
+    pub class: String,
+    pub process: String,
+    pub location: WindowLocation,
+    pub floating: bool,
+    pub focused: bool,
+}
+
+impl Snapshot {
+    pub fn new(windows_manager: &WindowsManager, workspace_manager: &WorkspaceManager) -> Self {
+        let monitor_container = &workspace_manager.monitor_container;
+        let container = &workspace_manager.workspace_container;
+
+        let monitors = monitor_container
+            .monitors
+            .iter()
+            .map(|monitor| {
+                let workspace = container
+                    .get_workspace_for_monitor(monitor)
+                    .and_then(|index| container.get_workspace_at_index(index));
+
+                MonitorSnapshot::new(monitor, workspace)
+            })
+            .collect();
+
+        let workspaces = container
+            .workspaces()
+            .iter()
+            .enumerate()
+            .map(|(index, workspace)| {
+                let monitor = container
+                    .get_current_monitor_for_workspace(index)
+                    .map(|monitor| monitor.index);
+
+                WorkspaceSnapshot::new(index, workspace, monitor, windows_manager)
+            })
+            .collect();
+
+        Self {
+            focused_monitor: monitor_container.focused_monitor,
+            focused_window: windows_manager
+                .windows
+                .values()
+                .find(|window| window.is_focused())
+                .map(|window| window.handle),
+            monitors,
+            workspaces,
+        }
+    }
+}
+
+impl MonitorSnapshot {
+    fn new(monitor: &Monitor, workspace: Option<&Workspace>) -> Self {
+        Self {
+            index: monitor.index,
+            name: monitor.name().to_string(),
+            primary: monitor.screen.primary,
+            bounds: monitor.screen.bounds,
+            working_area: monitor.screen.working_area(),
+            workspace: workspace.map(|workspace| workspace.name.clone()),
+        }
+    }
+}
+
+impl WorkspaceSnapshot {
+    fn new(
+        index: usize,
+        workspace: &Workspace,
+        monitor: Option<usize>,
+        windows_manager: &WindowsManager,
+    ) -> Self {
+        Self {
+            index,
+            name: workspace.name.clone(),
+            monitor,
+            area: workspace.area(),
+            layout: workspace.layout(),
+            gaps: workspace.gaps(),
+            windows: workspace
+                .windows
+                .iter()
+                .map(|window| {
+                    let floating = windows_manager.floating.contains_key(&window.handle);
+                    WindowSnapshot::new(window, floating)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl WindowSnapshot {
+    fn new(window: &Window, floating: bool) -> Self {
+        Self {
+            handle: window.handle,
+            title: window.title(),
+            class: window.class(),
+            process: window.process_file_name().to_string(),
+            location: window.location(),
+            floating,
+            focused: window.is_focused(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::simulated_desktop::dual_monitor_desktop;
+    use crate::classes::action::Action;
+    use crate::ipc::protocol::{Query, Request, Response};
+
+    #[test]
+    fn state_snapshots_diff_by_what_changed() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let tiled = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        let floating =
+            desktop.open_window(1, "CASCADIA_HOSTING_WINDOW_CLASS", "Terminal", "wt.exe");
+        desktop.focus(floating);
+        desktop.pump(&mut app);
+
+        let Response::State(before) = app.handle_request(Request::Query(Query::State)) else {
+            panic!("expected the state");
+        };
+        assert_eq!(before.focused_window, Some(floating));
+        assert_eq!(before.monitors[1].workspace.as_deref(), Some("2"));
+        assert_eq!(before.workspaces[0].windows[0].handle, tiled);
+        assert_eq!(before.workspaces[0].monitor, Some(0));
+        assert_eq!(before.workspaces[2].monitor, None);
+
+        let text = serde_json::to_string(&before).unwrap();
+        assert_eq!(serde_json::from_str::<Snapshot>(&text).unwrap(), before);
+        assert_eq!(app.snapshot(), before);
+
+        app.handle_action(Action::ToggleFocusedWindowTiling);
+        desktop.pump(&mut app);
+
+        let after = app.snapshot();
+        let mut expected = before.clone();
+        let window = &mut expected.workspaces[1].windows[0];
+        window.floating = true;
+        window.location = after.workspaces[1].windows[0].location.clone();
+        assert_eq!(after, expected);
+    }
+}
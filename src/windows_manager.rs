@@ -1,50 +1,27 @@
+use crate::backends::{default_backend, WindowBackend, WindowEvent};
 use crate::classes::action::Action;
 use crate::classes::keys::Keys;
+use crate::classes::window_rules::{RuleActions, WindowRules};
+use crate::csharp::structs::Rectangle;
 use crate::delegates::{WindowCreateDelegate, WindowDelegate, WindowUpdateDelegate};
 use crate::helpers::event::Event;
-use crate::helpers::win32_helpers::is_app_window;
 use crate::helpers::windows_defer_pos_handle::WindowsDeferPosHandle;
-use crate::layout_engines;
-use crate::layout_engines::{LayoutEngine, LayoutEngineType};
 use crate::window::Window;
-use crossbeam_channel::{Receiver, Sender};
-use lazy_static::lazy_static;
-use log::{debug, error, info, trace};
-use std::collections::{BTreeMap, HashMap};
-use std::sync::Mutex;
-use windows::Win32::Foundation::{BOOL, HMODULE, HWND, LPARAM, LRESULT, TRUE, WPARAM};
-use windows::Win32::System::LibraryLoader::GetModuleHandleW;
-use windows::Win32::UI::Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK};
-use windows::Win32::UI::WindowsAndMessaging::{
-    BeginDeferWindowPos, CallNextHookEx, DispatchMessageW, EnumWindows, GetMessageW,
-    SetWindowsHookExW, TranslateMessage, UnhookWindowsHookEx, EVENT_OBJECT_CLOAKED,
-    EVENT_OBJECT_DESTROY, EVENT_OBJECT_LOCATIONCHANGE, EVENT_OBJECT_SHOW, EVENT_OBJECT_UNCLOAKED,
-    EVENT_SYSTEM_FOREGROUND, EVENT_SYSTEM_MINIMIZEEND, EVENT_SYSTEM_MINIMIZESTART,
-    EVENT_SYSTEM_MOVESIZEEND, EVENT_SYSTEM_MOVESIZESTART, MSG, WH_KEYBOARD_LL, WH_MOUSE_LL,
-    WINEVENT_OUTOFCONTEXT, WM_LBUTTONUP,
-};
-
-type EventType = (u32, isize);
-
-lazy_static! {
-    static ref EVENT: (Sender<EventType>, Receiver<EventType>) = crossbeam_channel::unbounded();
-}
-
-lazy_static! {
-    static ref KEYS: (Sender<Keys>, Receiver<Keys>) = crossbeam_channel::unbounded();
-}
-
-lazy_static! {
-    static ref MOUSE: (Sender<()>, Receiver<()>) = crossbeam_channel::unbounded();
-}
+use log::{debug, trace};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 
+#[allow(dead_code)]
 pub struct WindowsManager {
+    backend: Arc<dyn WindowBackend>,
+
     pub windows: BTreeMap<isize, Window>,
     pub floating: HashMap<isize, bool>,
+    window_rules: WindowRules,
+    initialized: bool,
 
     mouse_move_lock: Mutex<()>,
     mouse_move_window: Option<isize>,
-    layout_engine_type: LayoutEngineType,
 
     pub event_window_created: Event<WindowCreateDelegate>,
     pub event_window_destroyed: Event<WindowDelegate>,
@@ -56,13 +33,22 @@ pub struct WindowsManager {
 
 impl Default for WindowsManager {
     fn default() -> Self {
+        Self::new(default_backend())
+    }
+}
+
+impl WindowsManager {
+    pub fn new(backend: Arc<dyn WindowBackend>) -> Self {
         WindowsManager {
+            backend,
+
             windows: BTreeMap::new(),
             floating: HashMap::new(),
+            window_rules: WindowRules::default(),
+            initialized: false,
 
             mouse_move_lock: Mutex::new(()),
             mouse_move_window: None,
-            layout_engine_type: LayoutEngineType::Dwindle,
 
             event_window_created: Event::<WindowCreateDelegate>::new(),
             event_window_destroyed: Event::<WindowDelegate>::new(),
@@ -72,224 +58,130 @@ impl Default for WindowsManager {
             event_external_window_closed: Event::<WindowDelegate>::new(),
         }
     }
-}
 
-impl WindowsManager {
     #[allow(dead_code)]
-    pub fn test_layout(&mut self, layout_engine_type: LayoutEngineType) {
-        // TODO: Check if enabled
-
-        // TODO: Don't construct every time
-        let mut layout: Box<dyn LayoutEngine> = match layout_engine_type {
-            LayoutEngineType::Dwindle => {
-                Box::new(layout_engines::dwindle_layout_engine::DwindleLayoutEngine::new())
-            }
-            LayoutEngineType::Focus => {
-                Box::new(layout_engines::focus_layout_engine::FocusLayoutEngine::new())
-            }
-            LayoutEngineType::Full => {
-                Box::new(layout_engines::full_layout_engine::FullLayoutEngine::new())
-            }
-            LayoutEngineType::Grid => {
-                Box::new(layout_engines::grid_layout_engine::GridLayoutEngine::new())
-            }
-        };
+    pub fn backend(&self) -> &Arc<dyn WindowBackend> {
+        &self.backend
+    }
 
-        self.windows
-            .iter_mut()
-            .for_each(|(_, window)| window.show_in_current_state());
+    // drops the windows the rules now ignore and picks up the ones they no longer do,
+    // the other actions only apply to windows as they're registered
+    pub fn change_window_rules(&mut self, window_rules: WindowRules) {
+        self.window_rules = window_rules;
 
-        let window_data: Vec<_> = self
+        let ignored: Vec<_> = self
             .windows
-            .iter()
-            .map(|(&id, window)| (id, window.clone()))
+            .values()
+            .filter(|window| self.window_rules.ignores(window))
+            .map(|window| window.handle)
             .collect();
 
-        let calc = layout.calc_layout(
-            &window_data.iter().map(|(_, w)| w).collect::<Vec<_>>(),
-            3840,
-            2160,
-        );
-
-        debug!("calc: {:?}", calc);
-
-        let mut handle = self.defer_windows_pos(calc.len() as i32);
-
-        for (i, loc) in calc.iter().enumerate() {
-            let (_, window) = &window_data[i];
+        for hwnd in ignored {
+            debug!("change_window_rules | handle: 0x{:X} now ignored", &hwnd);
+            self.unregister_window(hwnd);
+        }
 
-            if !window.is_mouse_moving && !window.is_fullscreen() {
-                handle.defer_window_pos(window, loc);
-            }
+        if self.initialized {
+            self.register_app_windows();
         }
     }
 
-    pub fn init(&mut self, layout_engine_type: LayoutEngineType) {
-        self.change_layout(layout_engine_type);
-
-        info!("Initializing hooks");
-
-        let module_handle = unsafe {
-            GetModuleHandleW(None).unwrap_or_else(|e| {
-                error!("Failed GetModuleHandleW: {:?}", e);
-                std::process::exit(69);
-            })
-        };
-
-        let event_windows = [
-            Self::register_window_hook(EVENT_OBJECT_DESTROY, EVENT_OBJECT_SHOW, module_handle),
-            Self::register_window_hook(EVENT_OBJECT_CLOAKED, EVENT_OBJECT_UNCLOAKED, module_handle),
-            Self::register_window_hook(
-                EVENT_SYSTEM_MINIMIZESTART,
-                EVENT_SYSTEM_MINIMIZEEND,
-                module_handle,
-            ),
-            Self::register_window_hook(
-                EVENT_SYSTEM_MOVESIZESTART,
-                EVENT_SYSTEM_MOVESIZEEND,
-                module_handle,
-            ),
-            Self::register_window_hook(
-                EVENT_SYSTEM_FOREGROUND,
-                EVENT_SYSTEM_FOREGROUND,
-                module_handle,
-            ),
-            Self::register_window_hook(
-                EVENT_OBJECT_LOCATIONCHANGE,
-                EVENT_OBJECT_LOCATIONCHANGE,
-                module_handle,
-            ),
-        ];
-
-        let event_mouse = unsafe {
-            SetWindowsHookExW(WH_MOUSE_LL, Some(Self::mouse_callback), module_handle, 0)
-                .unwrap_or_else(|e| {
-                    error!("Failed SetWindowsHookExW[Mouse]: {:?}", e);
-                    std::process::exit(69);
-                })
-        };
-
-        let event_keyboard = unsafe {
-            SetWindowsHookExW(
-                WH_KEYBOARD_LL,
-                Some(Self::keyboard_callback),
-                module_handle,
-                0,
-            )
-            .unwrap_or_else(|e| {
-                error!("Failed SetWindowsHookExW[Keyboard]: {:?}", e);
-                std::process::exit(69);
-            })
-        };
-
-        info!("Initialized hooks");
-
-        let mut windows: Vec<HWND> = vec![];
-
-        unsafe {
-            let _ = EnumWindows(
-                Some(Self::enum_windows_callback),
-                LPARAM(&mut windows as *mut Vec<HWND> as isize),
-            );
-        };
+    pub fn init(&mut self) {
+        self.backend.init();
+        self.initialized = true;
+        self.register_app_windows();
+    }
 
-        for hwnd in windows.into_iter() {
-            if is_app_window(hwnd) {
-                self.register_window(hwnd.0);
+    fn register_app_windows(&mut self) {
+        for hwnd in self.backend.enumerate_windows() {
+            if self.backend.is_app_window(hwnd) {
+                self.register_window(hwnd);
             }
         }
-
-        let mut message = MSG::default();
-
-        // Handle close
-        std::thread::spawn(move || {
-            loop {
-                unsafe {
-                    if GetMessageW(&mut message, HWND(0), 0, 0).as_bool() {
-                        TranslateMessage(&message);
-                        DispatchMessageW(&message);
-                    } else {
-                        break;
-                    }
-                }
-            }
-
-            unsafe {
-                UnhookWindowsHookEx(event_mouse).unwrap_or_else(|e| {
-                    error!("Failed UnhookWindowsHookEx (mouse): {:?}", e);
-                })
-            };
-
-            unsafe {
-                UnhookWindowsHookEx(event_keyboard).unwrap_or_else(|e| {
-                    error!("Failed UnhookWindowsHookEx (keyboard): {:?}", e);
-                })
-            };
-
-            for hooks in event_windows.into_iter() {
-                unsafe {
-                    if !UnhookWinEvent(hooks).as_bool() {
-                        error!("Failed UnhookWinEvent");
-                    }
-                }
-            }
-        });
     }
 
     pub fn handle_window(&mut self) {
-        if let Ok((event, hwnd)) = EVENT.1.try_recv() {
+        if let Some((event, hwnd)) = self.backend.poll_window_event() {
             match event {
-                EVENT_OBJECT_SHOW => self.register_window(hwnd),
-                EVENT_OBJECT_DESTROY => self.unregister_window(hwnd),
-                EVENT_OBJECT_CLOAKED => self.update_window(hwnd, WindowUpdateType::Hide),
-                EVENT_OBJECT_UNCLOAKED => self.update_window(hwnd, WindowUpdateType::Show),
-                EVENT_SYSTEM_MINIMIZESTART => {
+                WindowEvent::Show => self.register_window(hwnd),
+                WindowEvent::Destroy => self.unregister_window(hwnd),
+                WindowEvent::Cloaked => self.update_window(hwnd, WindowUpdateType::Hide),
+                WindowEvent::Uncloaked => self.update_window(hwnd, WindowUpdateType::Show),
+                WindowEvent::MinimizeStart => {
                     self.update_window(hwnd, WindowUpdateType::MinimizeStart)
                 }
-                EVENT_SYSTEM_MINIMIZEEND => self.update_window(hwnd, WindowUpdateType::MinimizeEnd),
-                EVENT_SYSTEM_FOREGROUND => self.update_window(hwnd, WindowUpdateType::Foreground),
-                EVENT_SYSTEM_MOVESIZESTART => self.start_move_window(hwnd),
-                EVENT_SYSTEM_MOVESIZEEND => self.end_move_window(hwnd),
-                EVENT_OBJECT_LOCATIONCHANGE => self.window_move(hwnd),
-                _ => error!("handle_window | event_type: UNKNOWN({:?})", event),
+                WindowEvent::MinimizeEnd => self.update_window(hwnd, WindowUpdateType::MinimizeEnd),
+                WindowEvent::Foreground => self.update_window(hwnd, WindowUpdateType::Foreground),
+                WindowEvent::MoveSizeStart => self.start_move_window(hwnd),
+                WindowEvent::MoveSizeEnd => self.end_move_window(hwnd),
+                WindowEvent::LocationChange => self.window_move(hwnd),
             };
         }
     }
 
-    pub fn handle_keys(&mut self, key_bindings: &HashMap<Action, Keys>) {
-        if let Ok(keys) = KEYS.1.try_recv() {
-            let matching = key_bindings.iter().find(|(_, key)| *key == &keys);
+    // returns the action bound to the next pressed keys, if any
+    pub fn handle_keys(&mut self, key_bindings: &HashMap<Action, Keys>) -> Option<Action> {
+        let keys = self.backend.poll_keys()?;
 
-            if let Some((action, _)) = matching {
-                match action {
-                    Action::ToggleFocusedWindowTiling => {
-                        info!("action: ToggleFocusedWindowTiling");
-                        self.toggle_focused_window_tiling();
-                    }
-                }
-            }
-        }
+        key_bindings
+            .iter()
+            .find(|(_, key)| key.matches(&keys))
+            .map(|(action, _)| *action)
+    }
+
+    // the next pressed keys, without looking at the bindings
+    pub fn poll_keys(&mut self) -> Option<Keys> {
+        self.backend.poll_keys()
+    }
+
+    // every binding not passed through to the focused window is swallowed by the hook
+    pub fn change_key_bindings(
+        &mut self,
+        key_bindings: &HashMap<Action, Keys>,
+        passthrough: &HashSet<Action>,
+    ) {
+        let swallowed = key_bindings
+            .iter()
+            .filter(|(action, _)| !passthrough.contains(action))
+            .map(|(_, keys)| *keys)
+            .collect();
+
+        self.backend.set_swallowed_keys(swallowed);
     }
 
     pub fn handle_mouse(&mut self) {
-        if let Ok(_mouse) = MOUSE.1.try_recv() {
+        if let Some(_mouse) = self.backend.poll_mouse() {
             trace!("mouse_release")
         }
     }
 
-    pub fn change_layout(&mut self, layout_engine_type: LayoutEngineType) {
-        self.layout_engine_type = layout_engine_type;
-        info!("Changed layout engine: {:?}", &layout_engine_type);
+    #[allow(dead_code)]
+    fn defer_windows_pos(&self, count: usize) -> WindowsDeferPosHandle<'_> {
+        WindowsDeferPosHandle::new(self.backend.clone(), count)
     }
 
-    #[allow(dead_code)]
-    fn defer_windows_pos(&mut self, count: i32) -> WindowsDeferPosHandle {
-        let info = unsafe { BeginDeferWindowPos(count).unwrap() }; // TODO: Unwrap
+    pub fn close_focused_window(&mut self) {
+        if let Some(window) = self.windows.values().find(|w| w.is_focused()) {
+            window.close();
+        }
+    }
+
+    // moves the focused window into `working_area`, keeping its size where it fits
+    pub fn move_focused_window_to_area(&mut self, working_area: Rectangle) {
+        let Some(window) = self.windows.values().find(|w| w.is_focused()).cloned() else {
+            return;
+        };
 
-        WindowsDeferPosHandle::new(info)
+        let mut location = window.location();
+        location.width = location.width.min(working_area.width);
+        location.height = location.height.min(working_area.height);
+        location.x = working_area.x + (working_area.width - location.width) / 2;
+        location.y = working_area.y + (working_area.height - location.height) / 2;
+
+        let mut handle = self.defer_windows_pos(1);
+        handle.defer_window_pos(&window, &location);
     }
 
-    #[allow(dead_code)]
     pub fn toggle_focused_window_tiling(&mut self) {
         let hwnd_option = self
             .windows
@@ -300,14 +192,16 @@ impl WindowsManager {
         if let Some(hwnd) = hwnd_option {
             if let std::collections::hash_map::Entry::Vacant(e) = self.floating.entry(hwnd) {
                 e.insert(true);
-                self.handle_window_remove(hwnd);
+                self.event_window_updated
+                    .broadcast((self.windows[&hwnd].clone(), WindowUpdateType::Float));
 
                 if let Some(window) = self.windows.get_mut(&hwnd) {
                     window.bring_to_top();
                 }
             } else {
                 self.floating.remove(&hwnd);
-                self.handle_window_add(hwnd, false);
+                self.event_window_updated
+                    .broadcast((self.windows[&hwnd].clone(), WindowUpdateType::Tile));
             }
 
             if let Some(window) = self.windows.get_mut(&hwnd) {
@@ -316,20 +210,6 @@ impl WindowsManager {
         }
     }
 
-    fn register_window_hook(event_min: u32, event_max: u32, hmodule: HMODULE) -> HWINEVENTHOOK {
-        unsafe {
-            SetWinEventHook(
-                event_min,
-                event_max,
-                hmodule,
-                Some(Self::window_callback),
-                0,
-                0,
-                WINEVENT_OUTOFCONTEXT,
-            )
-        }
-    }
-
     fn register_window(&mut self, hwnd: isize) {
         if self.windows.contains_key(&hwnd) {
             trace!("register_window | handle: 0x{:X} already registered", &hwnd);
@@ -338,13 +218,28 @@ impl WindowsManager {
 
         trace!("register_window | handle: 0x{:X} not registered", &hwnd);
 
-        match Window::new(hwnd) {
-            Ok(window) => {
-                debug!("register_window | handle: 0x{:X} registered", &hwnd);
-                self.windows.insert(hwnd, window)
-            }
-            Err(_) => None,
+        let Ok(window) = Window::new(hwnd, self.backend.clone()) else {
+            return;
         };
+
+        let actions = self.window_rules.actions_for(&window);
+        if actions.ignore {
+            trace!(
+                "register_window | handle: 0x{:X} ignored by the rules",
+                &hwnd
+            );
+            return;
+        }
+
+        debug!(
+            "register_window | handle: 0x{:X} registered, rules: {:?}",
+            &hwnd, &actions
+        );
+        if actions.floats() {
+            self.floating.insert(hwnd, true);
+        }
+        self.windows.insert(hwnd, window);
+        self.handle_window_add(hwnd, true, actions);
     }
 
     fn unregister_window(&mut self, hwnd: isize) {
@@ -355,8 +250,9 @@ impl WindowsManager {
 
         trace!("unregister_window | handle: 0x{:X} registered", &hwnd);
 
-        self.windows.remove(&hwnd);
         self.handle_window_remove(hwnd);
+        self.windows.remove(&hwnd);
+        self.floating.remove(&hwnd);
     }
 
     fn update_window(&mut self, hwnd: isize, update_type: WindowUpdateType) {
@@ -410,16 +306,19 @@ impl WindowsManager {
         }
     }
 
+    #[allow(dead_code)]
     fn handle_window_focused(&mut self, handle: isize) {
         self.event_window_focused
             .broadcast(self.windows[&handle].clone());
     }
 
+    #[allow(dead_code)]
     fn handle_window_updated(&mut self, handle: isize) {
         self.event_external_window_update
             .broadcast(self.windows[&handle].clone());
     }
 
+    #[allow(dead_code)]
     fn handle_window_closed(&mut self, handle: isize) {
         self.event_external_window_closed
             .broadcast(self.windows[&handle].clone());
@@ -449,71 +348,15 @@ impl WindowsManager {
         }
     }
 
-    fn handle_window_add(&mut self, handle: isize, first_create: bool) {
+    fn handle_window_add(&mut self, handle: isize, first_create: bool, actions: RuleActions) {
         self.event_window_created
-            .broadcast((self.windows[&handle].clone(), first_create));
+            .broadcast((self.windows[&handle].clone(), first_create, actions));
     }
 
     fn handle_window_remove(&mut self, handle: isize) {
         self.event_window_destroyed
             .broadcast(self.windows[&handle].clone());
     }
-
-    unsafe extern "system" fn enum_windows_callback(hwnd: HWND, userdata: LPARAM) -> BOOL {
-        let windows = &mut *(userdata.0 as *mut Vec<HWND>);
-        windows.push(hwnd);
-
-        TRUE
-    }
-
-    unsafe extern "system" fn mouse_callback(
-        n_code: i32,
-        w_param: WPARAM,
-        l_param: LPARAM,
-    ) -> LRESULT {
-        if w_param.0 == WM_LBUTTONUP as usize && MOUSE.0.send(()).is_err() {
-            error!("mouse_callback | failed to send");
-        }
-
-        CallNextHookEx(None, n_code, w_param, l_param)
-    }
-
-    unsafe extern "system" fn keyboard_callback(
-        n_code: i32,
-        w_param: WPARAM,
-        l_param: LPARAM,
-    ) -> LRESULT {
-        if let Some(keys) = Keys::new(n_code, w_param, l_param) {
-            if KEYS.0.send(keys).is_err() {
-                error!("keyboard_callback | failed to send");
-            };
-        };
-
-        CallNextHookEx(None, n_code, w_param, l_param)
-    }
-
-    unsafe extern "system" fn window_callback(
-        _h_win_event_hook: HWINEVENTHOOK,
-        event_type: u32,
-        hwnd: HWND,
-        id_object: i32,
-        id_child: i32,
-        _id_event_thread: u32,
-        _dwms_event_time: u32,
-    ) {
-        let hwnd = hwnd.0;
-
-        if !(id_child == 0 && id_object == 0 && hwnd != 0) {
-            return;
-        }
-
-        if EVENT.0.send((event_type, hwnd)).is_err() {
-            error!(
-                "event_callback | failed to send | event_type: {:?}, hwnd: 0x{:X}",
-                event_type, hwnd
-            );
-        }
-    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -529,4 +372,167 @@ pub enum WindowUpdateType {
     MoveEnd,
     #[allow(dead_code)]
     Move,
+    Float,
+    Tile,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::simulated_desktop::{dual_monitor_desktop, toggle_tiling};
+    use crate::classes::keys::{Side, VirtualKey};
+
+    #[test]
+    fn toggle_tiling_floats_focused_window() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let handles: Vec<_> = (0..3)
+            .map(|_| desktop.open_window(1, "Notepad", "Notepad", "notepad.exe"))
+            .collect();
+
+        desktop.focus(handles[1]);
+        desktop.press(toggle_tiling());
+        desktop.pump(&mut app);
+
+        assert_eq!(
+            app.windows_manager.floating.keys().collect::<Vec<_>>(),
+            [&handles[1]]
+        );
+
+        desktop.press(toggle_tiling());
+        desktop.pump(&mut app);
+
+        assert!(app.windows_manager.floating.is_empty());
+    }
+
+    #[test]
+    fn sided_binding_ignores_the_other_side() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+        app.key_bindings
+            .insert(Action::ToggleFocusedWindowTiling, "ralt+t".parse().unwrap());
+
+        let handle = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.focus(handle);
+
+        for side in [Side::Left, Side::Right] {
+            desktop.press(Keys {
+                alt_side: side,
+                ..toggle_tiling()
+            });
+        }
+        desktop.pump(&mut app);
+
+        assert_eq!(
+            app.windows_manager.floating.keys().collect::<Vec<_>>(),
+            [&handle]
+        );
+    }
+
+    #[test]
+    fn bound_keys_are_swallowed_unless_passed_through() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let handle = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.focus(handle);
+
+        assert!(!desktop.press(toggle_tiling()));
+        assert!(desktop.press(Keys {
+            key: VirtualKey::T,
+            ..Default::default()
+        }));
+        desktop.pump(&mut app);
+        assert!(app.windows_manager.floating.contains_key(&handle));
+
+        app.passthrough.insert(Action::ToggleFocusedWindowTiling);
+        app.apply_key_bindings();
+
+        assert!(desktop.press(toggle_tiling()));
+        desktop.pump(&mut app);
+        assert!(app.windows_manager.floating.is_empty());
+    }
+
+    #[test]
+    fn filters_shell_windows() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        desktop.open_window(0, "Shell_TrayWnd", "Taskbar", "explorer.exe");
+        desktop.open_window(0, "Notepad", "", "notepad.exe");
+        desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.pump(&mut app);
+
+        assert_eq!(app.windows_manager.windows.len(), 1);
+    }
+
+    #[test]
+    fn destroy_and_cloak_unregister_windows() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let closed = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        let cloaked = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        let kept = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.pump(&mut app);
+
+        desktop.close_window(closed);
+        desktop.cloak(cloaked);
+        desktop.pump(&mut app);
+
+        assert_eq!(
+            app.windows_manager.windows.keys().collect::<Vec<_>>(),
+            [&kept]
+        );
+
+        desktop.uncloak(cloaked);
+        desktop.pump(&mut app);
+
+        assert!(app.windows_manager.windows.contains_key(&cloaked));
+    }
+
+    #[test]
+    fn minimize_and_restore_broadcast_updates() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+        let updates = app.windows_manager.event_window_updated.subscribe();
+
+        let handle = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.minimize(handle);
+        desktop.restore(handle);
+        desktop.pump(&mut app);
+
+        let received: Vec<_> = updates
+            .try_iter()
+            .map(|(window, update_type)| (window.handle, update_type))
+            .collect();
+
+        assert_eq!(
+            received,
+            [
+                (handle, WindowUpdateType::MinimizeStart),
+                (handle, WindowUpdateType::MinimizeEnd),
+            ]
+        );
+    }
+
+    #[test]
+    fn move_size_tracks_mouse_moving_window() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let handle = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.start_move(handle);
+        desktop.move_window(handle, Rectangle::new(100, 100, 800, 600));
+        desktop.pump(&mut app);
+
+        assert!(app.windows_manager.windows[&handle].is_mouse_moving);
+
+        desktop.end_move(handle);
+        desktop.pump(&mut app);
+
+        assert!(!app.windows_manager.windows[&handle].is_mouse_moving);
+        assert_eq!(desktop.rect(handle), Rectangle::new(100, 100, 800, 600));
+    }
 }
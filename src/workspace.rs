@@ -1,16 +1,27 @@
-// TODO
-
+use crate::classes::direction::Direction;
+use crate::classes::gaps::Gaps;
 use crate::classes::window_order::WindowOrder;
+use crate::classes::window_state::WindowState;
+use crate::classes::workspace_layout::WorkspaceLayout;
+use crate::csharp::structs::{Point, Rectangle};
+use crate::delegates::LayoutCompletedDelegate;
+use crate::helpers::event::Event;
+use crate::helpers::windows_defer_pos_handle::WindowsDeferPosHandle;
+use crate::layout_engines::bsp_layout_engine::BspLayoutEngine;
 use crate::layout_engines::dwindle_layout_engine::DwindleLayoutEngine;
 use crate::layout_engines::focus_layout_engine::FocusLayoutEngine;
 use crate::layout_engines::full_layout_engine::FullLayoutEngine;
-use crate::layout_engines::grid_layout_engine::GridLayoutEngine;
+use crate::layout_engines::grid_layout_engine::{FillOrder, GridLayoutEngine};
+use crate::layout_engines::panel_layout_engine::PanelLayoutEngine;
+use crate::layout_engines::tall_layout_engine::TallLayoutEngine;
 use crate::layout_engines::*;
 use crate::window::Window;
 use crate::windows_manager::WindowUpdateType;
-use std::collections::HashMap;
+use log::debug;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 
+#[allow(dead_code)]
 #[derive(Debug)]
 pub struct Workspace {
     pub name: String,
@@ -18,22 +29,36 @@ pub struct Workspace {
     pub managed_windows: Vec<Window>,
     pub layout_engine: LayoutEngineType,
     last_focused: Option<Window>,
+    floating: HashSet<isize>,
+    minimized_by_layout: HashSet<isize>,
+    area: Option<Rectangle>,
+    gaps: Gaps,
 
     layout_engines: HashMap<LayoutEngineType, Box<dyn LayoutEngine>>,
+
+    pub event_layout_completed: Event<LayoutCompletedDelegate>,
 }
 
+#[allow(dead_code)]
 impl Workspace {
     pub fn new(name: &str) -> Self {
         let mut layout_engines: HashMap<LayoutEngineType, Box<dyn LayoutEngine>> = HashMap::new();
+        layout_engines.insert(LayoutEngineType::Bsp, Box::new(BspLayoutEngine::new()));
         layout_engines.insert(
             LayoutEngineType::Dwindle,
             Box::new(DwindleLayoutEngine::new()),
         );
         layout_engines.insert(LayoutEngineType::Focus, Box::new(FocusLayoutEngine::new()));
         layout_engines.insert(LayoutEngineType::Full, Box::new(FullLayoutEngine::new()));
-        layout_engines.insert(LayoutEngineType::Grid, Box::new(GridLayoutEngine::new()));
-        //layout_engines.insert(LayoutEngineType::Focus, Box::new(PanelLayoutEngine::new()));
-        //layout_engines.insert(LayoutEngineType::Focus, Box::new(TallLayoutEngine::new()));
+        layout_engines.insert(
+            LayoutEngineType::Grid,
+            Box::new(GridLayoutEngine::new(FillOrder::default(), 0)),
+        );
+        layout_engines.insert(
+            LayoutEngineType::Panel,
+            Box::new(PanelLayoutEngine::new(Orientation::default())),
+        );
+        layout_engines.insert(LayoutEngineType::Tall, Box::new(TallLayoutEngine::new()));
 
         Self {
             name: name.to_string(),
@@ -41,10 +66,84 @@ impl Workspace {
             managed_windows: Vec::new(),
             layout_engine: LayoutEngineType::default(),
             last_focused: None,
+            floating: HashSet::new(),
+            minimized_by_layout: HashSet::new(),
+            area: None,
+            gaps: Gaps::default(),
             layout_engines,
+            event_layout_completed: Event::<LayoutCompletedDelegate>::new(),
         }
     }
 
+    // the working area of the monitor showing the workspace, `None` while it is hidden
+    pub fn set_area(&mut self, area: Option<Rectangle>) {
+        self.area = area;
+    }
+
+    pub fn area(&self) -> Option<Rectangle> {
+        self.area
+    }
+
+    pub fn gaps(&self) -> Gaps {
+        self.gaps
+    }
+
+    pub fn set_gaps(&mut self, gaps: Gaps) {
+        self.gaps = gaps;
+        self.do_layout();
+    }
+
+    pub fn set_layout_engine(&mut self, layout_engine: LayoutEngineType) {
+        self.layout_engine = layout_engine;
+        self.do_layout();
+    }
+
+    pub fn layout(&self) -> WorkspaceLayout {
+        let primary = self.layout_engines[&self.layout_engine].primary();
+
+        WorkspaceLayout {
+            layout_engine_type: self.layout_engine,
+            num_in_primary: primary.map(|(num_in_primary, _)| num_in_primary),
+            primary_percent: primary.map(|(_, primary_percent)| primary_percent),
+        }
+    }
+
+    pub fn set_layout(&mut self, layout: WorkspaceLayout) {
+        self.layout_engine = layout.layout_engine_type;
+
+        let engine = self.get_layout_engine();
+        if let Some((num_in_primary, primary_percent)) = engine.primary() {
+            if layout.num_in_primary.is_some() || layout.primary_percent.is_some() {
+                engine.set_primary(
+                    layout.num_in_primary.unwrap_or(num_in_primary),
+                    layout.primary_percent.unwrap_or(primary_percent),
+                );
+            }
+        }
+
+        self.do_layout();
+    }
+
+    pub fn set_panel_orientation(&mut self, orientation: Orientation) {
+        self.layout_engines.insert(
+            LayoutEngineType::Panel,
+            Box::new(PanelLayoutEngine::new(orientation)),
+        );
+        self.do_layout();
+    }
+
+    pub fn set_grid(&mut self, fill_order: FillOrder, columns: usize) {
+        self.layout_engines.insert(
+            LayoutEngineType::Grid,
+            Box::new(GridLayoutEngine::new(fill_order, columns)),
+        );
+        self.do_layout();
+    }
+
+    pub fn is_floating(&self, window: &Window) -> bool {
+        self.floating.contains(&window.handle)
+    }
+
     pub fn layout_name(&self) -> String {
         format!("{:?}", self.layout_engine)
     }
@@ -53,15 +152,26 @@ impl Workspace {
         self.windows.iter().find(|w| w.is_focused())
     }
 
-    pub fn add_window(&mut self, window: &Window, window_order: WindowOrder, layout: bool) {
+    // `position` in the layout order overrides the `window_order`
+    pub fn add_window(
+        &mut self,
+        window: &Window,
+        window_order: WindowOrder,
+        position: Option<usize>,
+        layout: bool,
+    ) {
         {
             if self.last_focused.is_none() || window.is_focused() {
                 self.last_focused = Some(window.clone());
             }
 
-            match window_order {
-                WindowOrder::NewWindowsLast => self.windows.push(window.clone()),
-                WindowOrder::NewWindowsFirst => self.windows.insert(0, window.clone()),
+            match (position, window_order) {
+                (Some(position), _) => {
+                    let index = position.min(self.windows.len());
+                    self.windows.insert(index, window.clone())
+                }
+                (None, WindowOrder::NewWindowsLast) => self.windows.push(window.clone()),
+                (None, WindowOrder::NewWindowsFirst) => self.windows.insert(0, window.clone()),
             }
         }
 
@@ -74,11 +184,11 @@ impl Workspace {
         let length = self.managed_windows.len();
 
         if let Some(last_focused) = &self.last_focused {
-            if last_focused == window {
+            if last_focused.handle == window.handle {
                 let next_index = self
                     .managed_windows
                     .iter()
-                    .position(|w| w == window)
+                    .position(|w| w.handle == window.handle)
                     .and_then(|i| {
                         if length > 1 {
                             Some((i + 1) % length)
@@ -91,7 +201,9 @@ impl Workspace {
             }
         }
 
-        self.windows.retain(|w| w != window);
+        self.windows.retain(|w| w.handle != window.handle);
+        self.floating.remove(&window.handle);
+        self.minimized_by_layout.remove(&window.handle);
 
         if layout {
             self.do_layout();
@@ -105,8 +217,20 @@ impl Workspace {
         layout: bool,
     ) {
         // DEFAULT layout: true
-        if window_update_type == WindowUpdateType::Foreground {
-            self.last_focused = Some(window.clone());
+        match window_update_type {
+            WindowUpdateType::Foreground => self.last_focused = Some(window.clone()),
+            WindowUpdateType::Float => {
+                self.floating.insert(window.handle);
+            }
+            WindowUpdateType::Tile => {
+                self.floating.remove(&window.handle);
+            }
+            WindowUpdateType::MoveStart | WindowUpdateType::MoveEnd => {
+                if let Some(w) = self.windows.iter_mut().find(|w| w.handle == window.handle) {
+                    w.is_mouse_moving = window.is_mouse_moving;
+                }
+            }
+            _ => {}
         }
 
         if layout {
@@ -120,12 +244,105 @@ impl Workspace {
         }
     }
 
+    pub fn focus_next_window(&mut self) {
+        match self.focused_index() {
+            Some(index) => self.windows[(index + 1) % self.windows.len()].focus(),
+            None => self.focus_last_focused_window(),
+        }
+    }
+
+    pub fn focus_previous_window(&mut self) {
+        let length = self.windows.len();
+
+        match self.focused_index() {
+            Some(index) => self.windows[(index + length - 1) % length].focus(),
+            None => self.focus_last_focused_window(),
+        }
+    }
+
+    pub fn focus_primary_window(&mut self) {
+        if let Some(window) = self.windows.first() {
+            window.focus();
+        }
+    }
+
+    // focuses the nearest window on the `direction` side of the focused one, measured
+    // between their centres with straying sideways counting double
+    pub fn focus_window_in_direction(&mut self, direction: Direction) {
+        let Some(focused) = self.focused_window().map(Self::center) else {
+            self.focus_last_focused_window();
+            return;
+        };
+
+        let nearest = self
+            .windows
+            .iter()
+            .filter(|window| !window.is_focused() && !window.is_minimized())
+            .filter_map(|window| {
+                let center = Self::center(window);
+                let (along, across) = direction.split(center.x - focused.x, center.y - focused.y);
+                (along > 0).then_some((along + 2 * across, window))
+            })
+            .min_by_key(|(distance, _)| *distance);
+
+        if let Some((_, window)) = nearest {
+            window.focus();
+        }
+    }
+
+    fn center(window: &Window) -> Point {
+        let location = window.location();
+        Rectangle::new(location.x, location.y, location.width, location.height).center()
+    }
+
+    pub fn swap_focus_and_next_window(&mut self) {
+        if let Some(index) = self.focused_index() {
+            self.swap_indices(index, (index + 1) % self.windows.len());
+        }
+    }
+
+    pub fn swap_focus_and_previous_window(&mut self) {
+        let length = self.windows.len();
+
+        if let Some(index) = self.focused_index() {
+            self.swap_indices(index, (index + length - 1) % length);
+        }
+    }
+
+    pub fn swap_focus_and_primary_window(&mut self) {
+        if let Some(index) = self.focused_index() {
+            self.swap_indices(index, 0);
+        }
+    }
+
     pub fn previous_layout_engine(&mut self) {
         self.layout_engine = self.layout_engine.previous();
+        self.do_layout();
     }
 
     pub fn next_layout_engine(&mut self) {
         self.layout_engine = self.layout_engine.next();
+        self.do_layout();
+    }
+
+    pub fn shrink_primary_area(&mut self) {
+        self.get_layout_engine().shrink_primary_area();
+        self.do_layout();
+    }
+
+    pub fn expand_primary_area(&mut self) {
+        self.get_layout_engine().expand_primary_area();
+        self.do_layout();
+    }
+
+    pub fn increment_num_in_primary(&mut self) {
+        self.get_layout_engine().increment_num_in_primary();
+        self.do_layout();
+    }
+
+    pub fn decrement_num_in_primary(&mut self) {
+        self.get_layout_engine().decrement_num_in_primary();
+        self.do_layout();
     }
 
     pub fn reset_layout(&mut self) {
@@ -133,6 +350,41 @@ impl Workspace {
         self.do_layout();
     }
 
+    fn focused_index(&self) -> Option<usize> {
+        self.windows.iter().position(|w| w.is_focused())
+    }
+
+    pub fn hide_windows(&mut self) {
+        self.windows.iter_mut().for_each(|w| w.hide());
+    }
+
+    pub fn show_windows(&mut self) {
+        self.windows
+            .iter_mut()
+            .for_each(|w| w.show_in_current_state());
+    }
+
+    // nothing in the workspace has focus, so return to where the user last was
+    pub fn focus_last_focused_window(&self) {
+        let window = self
+            .last_focused
+            .as_ref()
+            .and_then(|last| self.windows.iter().find(|w| w.handle == last.handle))
+            .or_else(|| self.windows.first());
+
+        if let Some(window) = window {
+            window.focus();
+        }
+    }
+
+    fn swap_indices(&mut self, left: usize, right: usize) {
+        if left != right {
+            let left = self.windows[left].clone();
+            let right = self.windows[right].clone();
+            self.swap_windows(&left, &right);
+        }
+    }
+
     fn swap_windows(&mut self, left: &Window, right: &Window) {
         {
             let left_idx = self.windows.iter().position(|w| w == left);
@@ -149,15 +401,57 @@ impl Workspace {
     }
 
     pub fn do_layout(&mut self) {
-        if let Some(_window) = &self.focused_window() {
-            // TODO: OnLayoutCompleted?.Invoke(this);
+        // windows minimized by a layout, like the unfocused ones in `Full`, are still managed
+        self.managed_windows = self
+            .windows
+            .iter()
+            .filter(|w| {
+                !self.floating.contains(&w.handle)
+                    && (!w.is_minimized() || self.minimized_by_layout.contains(&w.handle))
+                    && !w.is_fullscreen()
+                    && w.can_layout()
+            })
+            .cloned()
+            .collect();
+
+        // hidden workspaces are laid out once they are shown again
+        let Some(area) = self.area else {
             return;
+        };
+
+        let windows: Vec<_> = self.managed_windows.iter().collect();
+        let engine = self.layout_engines.get_mut(&self.layout_engine).unwrap();
+        let locations = self.gaps.apply(engine.calc_layout_in(&windows, area), area);
+
+        debug!(
+            "do_layout | workspace: {}, layout: {:?}, windows: {}",
+            self.name,
+            self.layout_engine,
+            windows.len()
+        );
+
+        for (window, location) in windows.iter().zip(locations.iter()) {
+            if location.state == WindowState::Minimized {
+                self.minimized_by_layout.insert(window.handle);
+            } else {
+                self.minimized_by_layout.remove(&window.handle);
+            }
         }
 
-        // let _windows = self.managed_windows.clone();
+        if let Some(first) = windows.first() {
+            let mut handle = WindowsDeferPosHandle::new(first.backend().clone(), windows.len());
 
-        // TODO: _context.Enabled
-        if true {}
+            for (window, location) in windows.iter().zip(locations.iter()) {
+                if !window.is_mouse_moving {
+                    handle.defer_window_pos(window, location);
+                }
+            }
+        }
+
+        let placements = windows.iter().map(|w| w.handle).zip(locations).collect();
+
+        self.event_layout_completed
+            .broadcast((self.name.clone(), placements));
     }
 
     fn get_layout_engine(&mut self) -> &mut Box<dyn LayoutEngine> {
@@ -170,3 +464,273 @@ impl Display for Workspace {
         write!(f, "{}", self.name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::App;
+    use crate::backends::simulated_desktop::{dual_monitor_desktop, set_layout, toggle_tiling};
+    use crate::backends::ShowCommand;
+    use crate::classes::action::Action;
+    use crate::classes::window_location::WindowLocation;
+    use crate::context::context;
+    use crate::layout_engines::test_windows;
+
+    fn workspace(windows: &[Window]) -> Workspace {
+        let mut workspace = Workspace::new("test");
+        for window in windows {
+            workspace.add_window(window, WindowOrder::NewWindowsLast, None, false);
+        }
+        workspace
+    }
+
+    fn handles(workspace: &Workspace) -> Vec<isize> {
+        workspace.windows.iter().map(|w| w.handle).collect()
+    }
+
+    #[test]
+    fn focus_wraps_around() {
+        let windows = test_windows(3);
+        let mut workspace = workspace(&windows);
+
+        windows[2].focus();
+        workspace.focus_next_window();
+        assert!(windows[0].is_focused());
+
+        workspace.focus_previous_window();
+        assert!(windows[2].is_focused());
+
+        workspace.focus_primary_window();
+        assert!(windows[0].is_focused());
+    }
+
+    #[test]
+    fn swap_moves_focused_window() {
+        let windows = test_windows(3);
+        let mut workspace = workspace(&windows);
+        let [a, b, c] = [windows[0].handle, windows[1].handle, windows[2].handle];
+
+        windows[0].focus();
+        workspace.swap_focus_and_next_window();
+        assert_eq!(handles(&workspace), [b, a, c]);
+
+        workspace.swap_focus_and_previous_window();
+        assert_eq!(handles(&workspace), [a, b, c]);
+
+        workspace.swap_focus_and_previous_window();
+        assert_eq!(handles(&workspace), [c, b, a]);
+
+        windows[1].focus();
+        workspace.swap_focus_and_primary_window();
+        assert_eq!(handles(&workspace), [b, c, a]);
+    }
+
+    #[test]
+    fn layout_skips_floating_and_minimized_windows() {
+        let windows = test_windows(4);
+        let mut workspace = workspace(&windows);
+        let layouts = workspace.event_layout_completed.subscribe();
+
+        workspace.set_area(Some(Rectangle::new(0, 0, 1200, 800)));
+        workspace.update_window(&windows[1], WindowUpdateType::Float, false);
+        windows[2]
+            .backend()
+            .show(windows[2].handle, ShowCommand::Minimize);
+        workspace.set_layout_engine(LayoutEngineType::Tall);
+
+        assert_eq!(
+            workspace
+                .managed_windows
+                .iter()
+                .map(|w| w.handle)
+                .collect::<Vec<_>>(),
+            [windows[0].handle, windows[3].handle]
+        );
+
+        let (name, placements) = layouts.try_iter().last().unwrap();
+        assert_eq!(name, "test");
+        assert_eq!(
+            placements,
+            [
+                (
+                    windows[0].handle,
+                    WindowLocation::new(0, 0, 600, 800, WindowState::Normal)
+                ),
+                (
+                    windows[3].handle,
+                    WindowLocation::new(600, 0, 600, 800, WindowState::Normal)
+                ),
+            ]
+        );
+        assert_eq!(
+            windows[3].backend().rect(windows[3].handle),
+            Rectangle::new(600, 0, 600, 800)
+        );
+    }
+
+    #[test]
+    fn hidden_workspace_does_not_move_windows() {
+        let windows = test_windows(1);
+        let mut workspace = workspace(&windows);
+
+        workspace.set_layout_engine(LayoutEngineType::Tall);
+
+        assert_eq!(
+            windows[0].backend().rect(windows[0].handle),
+            Rectangle::new(0, 0, 800, 600)
+        );
+    }
+
+    #[test]
+    fn layout_round_trips_primary_area() {
+        let windows = test_windows(2);
+        let mut workspace = workspace(&windows);
+
+        workspace.set_area(Some(Rectangle::new(0, 0, 1000, 800)));
+        workspace.set_layout(WorkspaceLayout {
+            layout_engine_type: LayoutEngineType::Tall,
+            num_in_primary: Some(1),
+            primary_percent: Some(0.7),
+        });
+
+        assert_eq!(
+            windows[0].backend().rect(windows[0].handle),
+            Rectangle::new(0, 0, 700, 800)
+        );
+
+        workspace.expand_primary_area();
+        let layout = workspace.layout();
+        assert_eq!(layout.layout_engine_type, LayoutEngineType::Tall);
+        assert_eq!(layout.num_in_primary, Some(1));
+        assert!(layout.primary_percent.unwrap() > 0.7);
+
+        workspace.set_layout(WorkspaceLayout::default());
+        assert_eq!(workspace.layout(), WorkspaceLayout::default());
+    }
+
+    #[test]
+    fn full_layout_minimizes_unfocused_windows() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let focused = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        let other = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.focus(focused);
+        desktop.pump(&mut app);
+
+        set_layout(&mut app, LayoutEngineType::Full);
+
+        assert_eq!(desktop.rect(focused), Rectangle::new(0, 0, 1920, 1040));
+        assert!(!desktop.is_minimized(focused));
+        assert!(desktop.is_minimized(other));
+    }
+
+    #[test]
+    fn tiles_inside_each_monitor_working_area() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let primary = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        let secondary: Vec<_> = (0..3)
+            .map(|_| desktop.open_window(1, "Notepad", "Notepad", "notepad.exe"))
+            .collect();
+        desktop.pump(&mut app);
+
+        set_layout(&mut app, LayoutEngineType::Tall);
+
+        assert_eq!(desktop.rect(primary), Rectangle::new(0, 0, 1920, 1040));
+        assert_eq!(
+            secondary
+                .iter()
+                .map(|&h| desktop.rect(h))
+                .collect::<Vec<_>>(),
+            [
+                Rectangle::new(-2560, -360, 1280, 1400),
+                Rectangle::new(-1280, -360, 1280, 700),
+                Rectangle::new(-1280, 340, 1280, 700),
+            ]
+        );
+    }
+
+    #[test]
+    fn focus_moves_to_the_nearest_window_in_a_direction() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let handles: Vec<_> = (0..3)
+            .map(|_| desktop.open_window(0, "Notepad", "Notepad", "notepad.exe"))
+            .collect();
+        desktop.focus(handles[0]);
+        desktop.pump(&mut app);
+
+        set_layout(&mut app, LayoutEngineType::Tall);
+
+        let mut focus = |direction| {
+            app.handle_action(Action::FocusWindowInDirection(direction));
+            desktop.pump(&mut app);
+            handles
+                .iter()
+                .position(|&handle| desktop.foreground_window() == handle)
+        };
+
+        assert_eq!(focus(Direction::Left), Some(0));
+        assert_eq!(focus(Direction::Right), Some(1));
+        assert_eq!(focus(Direction::Down), Some(2));
+        assert_eq!(focus(Direction::Right), Some(2));
+        assert_eq!(focus(Direction::Up), Some(1));
+        assert_eq!(focus(Direction::Left), Some(0));
+    }
+
+    #[test]
+    fn floating_window_leaves_the_layout() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+        set_layout(&mut app, LayoutEngineType::Tall);
+
+        let tiled = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        let floating = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.pump(&mut app);
+
+        assert_eq!(desktop.rect(tiled), Rectangle::new(0, 0, 960, 1040));
+
+        desktop.focus(floating);
+        desktop.press(toggle_tiling());
+        desktop.pump(&mut app);
+
+        assert_eq!(desktop.rect(tiled), Rectangle::new(0, 0, 1920, 1040));
+        assert_eq!(desktop.rect(floating), Rectangle::new(960, 0, 960, 1040));
+        assert_eq!(
+            app.workspace_manager.workspace_for_window(floating),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn workspace_layouts_restore_from_settings() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = App::with_backend(desktop.backend());
+        app.settings.workspace_layouts.insert(
+            "2".to_string(),
+            WorkspaceLayout {
+                layout_engine_type: LayoutEngineType::Tall,
+                num_in_primary: Some(1),
+                primary_percent: Some(0.25),
+            },
+        );
+        context(&mut app);
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| desktop.open_window(1, "Notepad", "Notepad", "notepad.exe"))
+            .collect();
+        desktop.pump(&mut app);
+
+        assert_eq!(
+            desktop.rect(handles[0]),
+            Rectangle::new(-2560, -360, 640, 1400)
+        );
+
+        let layouts = app.workspace_manager.workspace_layouts();
+        assert_eq!(layouts["1"].layout_engine_type, LayoutEngineType::Full);
+        assert_eq!(layouts["2"], app.settings.workspace_layouts["2"]);
+    }
+}
@@ -1,23 +1,247 @@
 use crate::classes::monitor::Monitor;
 use crate::workspace::Workspace;
+use log::{debug, warn};
 use std::collections::HashMap;
 
 #[derive(Debug, Default)]
 pub struct WorkspaceContainer {
     workspaces: Vec<Workspace>,
-    workspace_map: HashMap<Workspace, usize>,
+    workspace_map: HashMap<String, usize>,
     monitor_to_workspace_map: HashMap<Monitor, usize>,
     last_monitor: HashMap<usize, Monitor>,
 }
 
+#[allow(dead_code)]
 impl WorkspaceContainer {
-    pub fn create_workspaces(&mut self, names: Vec<&str>) {
+    pub fn create_workspaces(&mut self, names: &[String]) {
         for name in names {
             self.create_workspace(name);
         }
     }
 
-    pub fn create_workspace(&mut self, _name: &str) {
-        // TODO
+    // returns the index of the workspace, names are unique so an existing one is reused
+    pub fn create_workspace(&mut self, name: &str) -> usize {
+        if let Some(index) = self.workspace_map.get(name) {
+            warn!("create_workspace | workspace {} already exists", name);
+            return *index;
+        }
+
+        self.workspaces.push(Workspace::new(name));
+        let index = self.workspaces.len() - 1;
+        self.workspace_map.insert(name.to_string(), index);
+
+        debug!("create_workspace | name: {}, index: {}", name, index);
+        index
+    }
+
+    // gives every monitor its own workspace, creating numbered ones when there are too few
+    pub fn assign_workspaces(&mut self, monitors: &[Monitor]) {
+        self.monitor_to_workspace_map.clear();
+
+        for (index, monitor) in monitors.iter().enumerate() {
+            while self.workspaces.len() <= index {
+                let name = (self.workspaces.len() + 1).to_string();
+                self.create_workspace(&name);
+            }
+
+            self.assign_workspace_to_monitor(index, monitor);
+        }
+    }
+
+    pub fn assign_workspace_to_monitor(&mut self, workspace: usize, monitor: &Monitor) {
+        self.last_monitor.insert(workspace, monitor.clone());
+        self.monitor_to_workspace_map
+            .insert(monitor.clone(), workspace);
+        self.refresh_areas();
+    }
+
+    // hands every workspace the working area of the monitor it is shown on
+    fn refresh_areas(&mut self) {
+        let areas: Vec<_> = (0..self.workspaces.len())
+            .map(|index| {
+                self.get_current_monitor_for_workspace(index)
+                    .map(|monitor| monitor.screen.working_area())
+            })
+            .collect();
+
+        for (workspace, area) in self.workspaces.iter_mut().zip(areas) {
+            workspace.set_area(area);
+        }
+    }
+
+    // Shows `workspace` on `monitor`. A workspace visible on another monitor trades places
+    // with the one currently on `monitor`. Returns the workspace previously on `monitor`.
+    pub fn switch_monitor_to_workspace(
+        &mut self,
+        monitor: &Monitor,
+        workspace: usize,
+    ) -> Option<usize> {
+        if workspace >= self.workspaces.len() {
+            warn!(
+                "switch_monitor_to_workspace | no workspace at {}",
+                workspace
+            );
+            return None;
+        }
+
+        let current = self.get_workspace_for_monitor(monitor);
+        if current == Some(workspace) {
+            return current;
+        }
+
+        match (
+            current,
+            self.get_current_monitor_for_workspace(workspace).cloned(),
+        ) {
+            (Some(current), Some(other_monitor)) => {
+                self.assign_workspace_to_monitor(current, &other_monitor);
+            }
+            (None, Some(other_monitor)) => {
+                self.monitor_to_workspace_map.remove(&other_monitor);
+            }
+            _ => {}
+        }
+
+        self.assign_workspace_to_monitor(workspace, monitor);
+        current
+    }
+
+    pub fn workspaces(&self) -> &[Workspace] {
+        &self.workspaces
+    }
+
+    pub fn workspaces_mut(&mut self) -> &mut [Workspace] {
+        &mut self.workspaces
+    }
+
+    pub fn get_workspace_at_index(&self, index: usize) -> Option<&Workspace> {
+        self.workspaces.get(index)
+    }
+
+    pub fn get_workspace_at_index_mut(&mut self, index: usize) -> Option<&mut Workspace> {
+        self.workspaces.get_mut(index)
+    }
+
+    pub fn get_workspace_by_name(&self, name: &str) -> Option<usize> {
+        self.workspace_map.get(name).copied()
+    }
+
+    pub fn get_next_workspace(&self, current: usize) -> usize {
+        (current + 1) % self.workspaces.len()
+    }
+
+    pub fn get_previous_workspace(&self, current: usize) -> usize {
+        (current + self.workspaces.len() - 1) % self.workspaces.len()
+    }
+
+    pub fn get_workspace_for_monitor(&self, monitor: &Monitor) -> Option<usize> {
+        self.monitor_to_workspace_map.get(monitor).copied()
+    }
+
+    pub fn get_current_monitor_for_workspace(&self, workspace: usize) -> Option<&Monitor> {
+        self.monitor_to_workspace_map
+            .iter()
+            .find(|(_, w)| **w == workspace)
+            .map(|(monitor, _)| monitor)
+    }
+
+    // the monitor the workspace is on, or the one it was last shown on
+    pub fn get_desired_monitor_for_workspace(&self, workspace: usize) -> Option<&Monitor> {
+        self.get_current_monitor_for_workspace(workspace)
+            .or_else(|| self.last_monitor.get(&workspace))
+    }
+
+    pub fn is_workspace_visible(&self, workspace: usize) -> bool {
+        self.get_current_monitor_for_workspace(workspace).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csharp::screen::Screen;
+
+    fn monitors(count: usize) -> Vec<Monitor> {
+        (0..count)
+            .map(|i| {
+                Monitor::new(
+                    i,
+                    Screen {
+                        device_name: format!("\\\\.\\DISPLAY{}", i + 1),
+                        primary: i == 0,
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn container(names: &[&str], monitors: &[Monitor]) -> WorkspaceContainer {
+        let mut container = WorkspaceContainer::default();
+        let names: Vec<_> = names.iter().map(|n| n.to_string()).collect();
+        container.create_workspaces(&names);
+        container.assign_workspaces(monitors);
+        container
+    }
+
+    #[test]
+    fn assigns_one_workspace_per_monitor() {
+        let monitors = monitors(2);
+        let container = container(&["main", "web", "chat"], &monitors);
+
+        assert_eq!(container.get_workspace_for_monitor(&monitors[0]), Some(0));
+        assert_eq!(container.get_workspace_for_monitor(&monitors[1]), Some(1));
+        assert_eq!(
+            container.get_current_monitor_for_workspace(1),
+            Some(&monitors[1])
+        );
+        assert_eq!(container.get_current_monitor_for_workspace(2), None);
+        assert_eq!(container.get_workspace_by_name("chat"), Some(2));
+    }
+
+    #[test]
+    fn creates_workspaces_for_extra_monitors() {
+        let monitors = monitors(3);
+        let container = container(&["main"], &monitors);
+
+        assert_eq!(container.workspaces().len(), 3);
+        assert_eq!(container.get_workspace_by_name("3"), Some(2));
+        assert_eq!(container.get_workspace_for_monitor(&monitors[2]), Some(2));
+    }
+
+    #[test]
+    fn switching_to_hidden_workspace_hides_current() {
+        let monitors = monitors(2);
+        let mut container = container(&["main", "web", "chat"], &monitors);
+
+        assert_eq!(
+            container.switch_monitor_to_workspace(&monitors[0], 2),
+            Some(0)
+        );
+        assert_eq!(container.get_workspace_for_monitor(&monitors[0]), Some(2));
+        assert!(!container.is_workspace_visible(0));
+        assert_eq!(
+            container.get_desired_monitor_for_workspace(0),
+            Some(&monitors[0])
+        );
+    }
+
+    #[test]
+    fn switching_to_visible_workspace_swaps_monitors() {
+        let monitors = monitors(2);
+        let mut container = container(&["main", "web", "chat"], &monitors);
+
+        assert_eq!(
+            container.switch_monitor_to_workspace(&monitors[0], 1),
+            Some(0)
+        );
+        assert_eq!(container.get_workspace_for_monitor(&monitors[0]), Some(1));
+        assert_eq!(container.get_workspace_for_monitor(&monitors[1]), Some(0));
+
+        assert_eq!(
+            container.switch_monitor_to_workspace(&monitors[0], 1),
+            Some(1)
+        );
+        assert_eq!(container.get_workspace_for_monitor(&monitors[1]), Some(0));
     }
 }
@@ -1,16 +1,680 @@
-use crate::delegates::{WindowCreateDelegate, WindowDelegate};
+use crate::backends::{default_backend, WindowBackend};
+use crate::classes::gaps::Gaps;
+use crate::classes::native_monitor_container::NativeMonitorContainer;
+use crate::classes::window_order::WindowOrder;
+use crate::classes::window_rules::RuleActions;
+use crate::classes::workspace_layout::WorkspaceLayout;
+use crate::csharp::structs::Size;
+use crate::delegates::{
+    LayoutCompletedDelegate, MonitorDelegate, WindowCreateDelegate, WindowDelegate,
+    WindowUpdateDelegate, WorkspaceSwitchedDelegate,
+};
+use crate::helpers::event::Event;
+use crate::helpers::windows_defer_pos_handle::WindowsDeferPosHandle;
+use crate::layout_engines::grid_layout_engine::FillOrder;
+use crate::layout_engines::Orientation;
 use crate::window::Window;
-use crossbeam_channel::Receiver;
+use crate::windows_manager::{WindowUpdateType, WindowsManager};
+use crate::workspace::Workspace;
+use crate::workspace_container::WorkspaceContainer;
+use crossbeam_channel::{unbounded, Receiver};
+use log::{debug, info, trace};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 
-#[derive(Default)]
 pub struct WorkspaceManager {
-    window_manager: Option<Receiver<WindowDelegate>>,
+    window_created: Option<Receiver<WindowCreateDelegate>>,
+    window_destroyed: Option<Receiver<WindowDelegate>>,
+    window_updated: Option<Receiver<WindowUpdateDelegate>>,
+
+    pub monitor_container: NativeMonitorContainer,
+    pub workspace_container: WorkspaceContainer,
+
+    window_to_workspace: HashMap<isize, usize>,
+    new_window_order: WindowOrder,
+
+    pub event_workspace_switched: Event<WorkspaceSwitchedDelegate>,
+    pub event_monitor_focused: Event<MonitorDelegate>,
+}
+
+impl Default for WorkspaceManager {
+    fn default() -> Self {
+        Self::new(default_backend())
+    }
 }
 
 impl WorkspaceManager {
-    pub fn add_window_manager(&mut self, window_create_delegate: WindowCreateDelegate) {
-        self.add_window(window_create_delegate.0, false, window_create_delegate.1);
+    pub fn new(backend: Arc<dyn WindowBackend>) -> Self {
+        Self {
+            window_created: None,
+            window_destroyed: None,
+            window_updated: None,
+
+            monitor_container: NativeMonitorContainer::new(backend),
+            workspace_container: WorkspaceContainer::default(),
+
+            window_to_workspace: HashMap::new(),
+            new_window_order: WindowOrder::default(),
+
+            event_workspace_switched: Event::<WorkspaceSwitchedDelegate>::new(),
+            event_monitor_focused: Event::<MonitorDelegate>::new(),
+        }
+    }
+
+    pub fn add_window_manager(&mut self, windows_manager: &mut WindowsManager) {
+        self.window_created = Some(windows_manager.event_window_created.subscribe());
+        self.window_destroyed = Some(windows_manager.event_window_destroyed.subscribe());
+        self.window_updated = Some(windows_manager.event_window_updated.subscribe());
+    }
+
+    // creates the workspaces and shows one on every monitor
+    pub fn create_workspaces(&mut self, names: &[String]) {
+        self.workspace_container.create_workspaces(names);
+        self.workspace_container
+            .assign_workspaces(self.monitor_container.get_all_monitors());
+    }
+
+    // one receiver for the layouts of every workspace, subscribe after creating the workspaces
+    pub fn subscribe_layout_completed(&mut self) -> Receiver<LayoutCompletedDelegate> {
+        let (sender, receiver) = unbounded();
+
+        for workspace in self.workspace_container.workspaces_mut() {
+            workspace
+                .event_layout_completed
+                .add_subscriber(sender.clone());
+        }
+
+        receiver
+    }
+
+    pub fn change_window_order(&mut self, window_order: WindowOrder) {
+        self.new_window_order = window_order;
+    }
+
+    // the current layout of every workspace by name
+    pub fn workspace_layouts(&self) -> BTreeMap<String, WorkspaceLayout> {
+        self.workspace_container
+            .workspaces()
+            .iter()
+            .map(|w| (w.name.clone(), w.layout()))
+            .collect()
+    }
+
+    pub fn change_workspace_layouts(&mut self, layouts: &BTreeMap<String, WorkspaceLayout>) {
+        for workspace in self.workspace_container.workspaces_mut() {
+            if let Some(layout) = layouts.get(&workspace.name) {
+                workspace.set_layout(*layout);
+            }
+        }
+        info!("Changed workspace layouts: {:?}", layouts);
+    }
+
+    pub fn change_gaps(&mut self, gaps: Gaps) {
+        self.workspace_container
+            .workspaces_mut()
+            .iter_mut()
+            .for_each(|w| w.set_gaps(gaps));
+        info!("Changed gaps: {:?}", &gaps);
+    }
+
+    pub fn change_grid(&mut self, fill_order: FillOrder, columns: usize) {
+        self.workspace_container
+            .workspaces_mut()
+            .iter_mut()
+            .for_each(|w| w.set_grid(fill_order, columns));
+        info!("Changed grid: {:?}, columns: {}", &fill_order, columns);
+    }
+
+    pub fn change_panel_orientation(&mut self, orientation: Orientation) {
+        self.workspace_container
+            .workspaces_mut()
+            .iter_mut()
+            .for_each(|w| w.set_panel_orientation(orientation));
+        info!("Changed panel orientation: {:?}", &orientation);
+    }
+
+    // drains the window events broadcast by the `WindowsManager`
+    pub fn handle_events(&mut self) {
+        let created: Vec<_> = Self::drain(&self.window_created);
+        for (window, first_create, actions) in created {
+            self.add_window(window, true, first_create, &actions);
+        }
+
+        let updated: Vec<_> = Self::drain(&self.window_updated);
+        for (window, update_type) in updated {
+            self.update_window(&window, update_type);
+        }
+
+        let destroyed: Vec<_> = Self::drain(&self.window_destroyed);
+        for window in destroyed {
+            self.remove_window(&window);
+        }
+    }
+
+    fn drain<T>(receiver: &Option<Receiver<T>>) -> Vec<T> {
+        receiver
+            .as_ref()
+            .map(|receiver| receiver.try_iter().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn workspace_for_window(&self, handle: isize) -> Option<usize> {
+        self.window_to_workspace.get(&handle).copied()
+    }
+
+    pub fn focused_workspace(&self) -> Option<usize> {
+        self.workspace_container
+            .get_workspace_for_monitor(self.monitor_container.focused_monitor())
+    }
+
+    pub fn focused_workspace_mut(&mut self) -> Option<&mut Workspace> {
+        let index = self.focused_workspace()?;
+        self.workspace_container.get_workspace_at_index_mut(index)
+    }
+
+    // the workspace shown on the monitor the window is on
+    fn workspace_for_location(&self, window: &Window) -> Option<usize> {
+        let location = window.location();
+        let monitor = self.monitor_container.get_monitor_at_rect(
+            location.x,
+            location.y,
+            location.width,
+            location.height,
+        );
+
+        self.workspace_container
+            .get_workspace_for_monitor(monitor)
+            .or_else(|| self.focused_workspace())
+    }
+
+    // the workspace the rules assign by name, or else the one shown on the monitor they assign
+    fn workspace_for_rules(&self, actions: &RuleActions) -> Option<usize> {
+        actions
+            .workspace
+            .as_deref()
+            .and_then(|name| self.workspace_container.get_workspace_by_name(name))
+            .or_else(|| {
+                let monitor = self
+                    .monitor_container
+                    .get_monitor_at_index(actions.monitor?)?;
+                self.workspace_container.get_workspace_for_monitor(monitor)
+            })
+    }
+
+    pub fn add_window(
+        &mut self,
+        window: Window,
+        switch_to_workspace: bool,
+        first_create: bool,
+        actions: &RuleActions,
+    ) {
+        if self.window_to_workspace.contains_key(&window.handle) {
+            trace!("add_window | handle: 0x{:X} already added", window.handle);
+            return;
+        }
+
+        // windows the rules send to a hidden workspace wait there
+        let (index, switch) = match self.workspace_for_rules(actions) {
+            Some(index) => (index, false),
+            None => match self.workspace_for_location(&window) {
+                Some(index) => (index, switch_to_workspace),
+                None => return,
+            },
+        };
+
+        debug!(
+            "add_window | handle: 0x{:X}, workspace: {}, first_create: {}",
+            window.handle, index, first_create
+        );
+
+        self.add_window_to_workspace(window, index, switch, actions);
+    }
+
+    fn add_window_to_workspace(
+        &mut self,
+        mut window: Window,
+        index: usize,
+        switch: bool,
+        actions: &RuleActions,
+    ) {
+        let visible = self.workspace_container.is_workspace_visible(index);
+
+        if !visible && !switch {
+            window.hide();
+        }
+
+        if let Some(size) = actions.size {
+            self.resize_window(&window, index, size);
+        }
+
+        self.window_to_workspace.insert(window.handle, index);
+
+        if let Some(workspace) = self.workspace_container.get_workspace_at_index_mut(index) {
+            if actions.floats() {
+                workspace.update_window(&window, WindowUpdateType::Float, false);
+            }
+            workspace.add_window(&window, self.new_window_order, actions.position, visible);
+        }
+
+        if !visible && switch {
+            self.switch_to_workspace(index);
+        }
+    }
+
+    // centres the window on the monitor of the workspace, where it has been on one
+    fn resize_window(&self, window: &Window, index: usize, size: Size) {
+        let mut location = window.location();
+        location.width = size.width;
+        location.height = size.height;
+
+        if let Some(monitor) = self
+            .workspace_container
+            .get_desired_monitor_for_workspace(index)
+        {
+            let area = monitor.screen.working_area();
+            location.x = area.x + (area.width - size.width) / 2;
+            location.y = area.y + (area.height - size.height) / 2;
+        }
+
+        let mut handle = WindowsDeferPosHandle::new(window.backend().clone(), 1);
+        handle.defer_window_pos(window, &location);
+    }
+
+    pub fn remove_window(&mut self, window: &Window) {
+        let Some(index) = self.window_to_workspace.remove(&window.handle) else {
+            return;
+        };
+
+        debug!(
+            "remove_window | handle: 0x{:X}, workspace: {}",
+            window.handle, index
+        );
+
+        let visible = self.workspace_container.is_workspace_visible(index);
+        if let Some(workspace) = self.workspace_container.get_workspace_at_index_mut(index) {
+            workspace.remove_window(window, visible);
+        }
+    }
+
+    pub fn update_window(&mut self, window: &Window, update_type: WindowUpdateType) {
+        let Some(index) = self.workspace_for_window(window.handle) else {
+            return;
+        };
+
+        match update_type {
+            // the layout is redone once the window is dropped
+            WindowUpdateType::Move => return,
+            WindowUpdateType::MoveEnd => {
+                if let Some(target) = self.workspace_for_location(window) {
+                    if target != index {
+                        self.move_window_to_workspace(window.handle, target);
+                        return;
+                    }
+                }
+            }
+            WindowUpdateType::Foreground => {
+                if let Some(monitor) = self
+                    .workspace_container
+                    .get_current_monitor_for_workspace(index)
+                {
+                    if self.monitor_container.focused_monitor != monitor.index {
+                        self.monitor_container.focused_monitor = monitor.index;
+                        self.event_monitor_focused.broadcast(monitor.index);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        let layout = update_type != WindowUpdateType::MoveStart
+            && self.workspace_container.is_workspace_visible(index);
+        if let Some(workspace) = self.workspace_container.get_workspace_at_index_mut(index) {
+            workspace.update_window(window, update_type, layout);
+        }
+    }
+
+    // shows workspace `index` on the focused monitor
+    pub fn switch_to_workspace(&mut self, index: usize) {
+        let monitor = self.monitor_container.focused_monitor().clone();
+        let previous = self
+            .workspace_container
+            .switch_monitor_to_workspace(&monitor, index);
+
+        if previous == Some(index)
+            || self
+                .workspace_container
+                .get_workspace_at_index(index)
+                .is_none()
+        {
+            return;
+        }
+
+        debug!(
+            "switch_to_workspace | monitor: {}, from: {:?}, to: {}",
+            monitor.index, previous, index
+        );
+        self.event_workspace_switched
+            .broadcast((monitor.index, previous, index));
+
+        if let Some(previous) = previous {
+            let visible = self.workspace_container.is_workspace_visible(previous);
+            if let Some(workspace) = self
+                .workspace_container
+                .get_workspace_at_index_mut(previous)
+            {
+                if visible {
+                    // swapped onto another monitor
+                    workspace.do_layout();
+                } else {
+                    workspace.hide_windows();
+                }
+            }
+        }
+
+        if let Some(workspace) = self.workspace_container.get_workspace_at_index_mut(index) {
+            workspace.show_windows();
+            workspace.do_layout();
+            workspace.focus_last_focused_window();
+        }
+    }
+
+    // windows on hidden workspaces would stay hidden once tile-rs is gone
+    pub fn show_all_windows(&mut self) {
+        for workspace in self.workspace_container.workspaces_mut() {
+            workspace.show_windows();
+        }
     }
 
-    pub fn add_window(&mut self, window: Window, switch_to_workspace: bool, first_create: bool) {}
+    pub fn move_focused_window_to_workspace(&mut self, index: usize) {
+        if let Some(handle) = self.focused_window() {
+            self.move_window_to_workspace(handle, index);
+        }
+    }
+
+    // hands the focused window to the workspace shown on monitor `index`
+    pub fn move_focused_window_to_monitor(&mut self, index: usize) {
+        let target = self
+            .monitor_container
+            .get_monitor_at_index(index)
+            .and_then(|monitor| self.workspace_container.get_workspace_for_monitor(monitor));
+
+        if let (Some(handle), Some(target)) = (self.focused_window(), target) {
+            self.move_window_to_workspace(handle, target);
+        }
+    }
+
+    fn focused_window(&self) -> Option<isize> {
+        self.workspace_container
+            .workspaces()
+            .iter()
+            .flat_map(|workspace| workspace.windows.iter())
+            .find(|window| window.is_focused())
+            .map(|window| window.handle)
+    }
+
+    fn move_window_to_workspace(&mut self, handle: isize, target: usize) {
+        let Some(current) = self.workspace_for_window(handle) else {
+            return;
+        };
+
+        if current == target
+            || self
+                .workspace_container
+                .get_workspace_at_index(target)
+                .is_none()
+        {
+            return;
+        }
+
+        debug!(
+            "move_window_to_workspace | handle: 0x{:X}, from: {}, to: {}",
+            handle, current, target
+        );
+
+        let visible = self.workspace_container.is_workspace_visible(current);
+        let Some(workspace) = self.workspace_container.get_workspace_at_index_mut(current) else {
+            return;
+        };
+        let Some(window) = workspace
+            .windows
+            .iter()
+            .find(|w| w.handle == handle)
+            .cloned()
+        else {
+            return;
+        };
+        // removing forgets the window floats, the target workspace has to float it too
+        let actions = RuleActions {
+            float: workspace.is_floating(&window),
+            ..Default::default()
+        };
+        workspace.remove_window(&window, visible);
+
+        self.add_window_to_workspace(window, target, false, &actions);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::simulated_desktop::{
+        alt, alt_shift, dual_monitor_desktop, set_layout, toggle_tiling,
+    };
+    use crate::classes::action::Action;
+    use crate::classes::keys::VirtualKey;
+    use crate::classes::window_rules::{Pattern, WindowRule};
+    use crate::csharp::structs::Rectangle;
+    use crate::layout_engines::LayoutEngineType;
+
+    #[test]
+    fn routes_windows_to_workspace_on_their_monitor() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let primary = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        let secondary = desktop.open_window(1, "Notepad", "Notepad", "notepad.exe");
+        desktop.pump(&mut app);
+
+        assert_eq!(app.workspace_manager.workspace_for_window(primary), Some(0));
+        assert_eq!(
+            app.workspace_manager.workspace_for_window(secondary),
+            Some(1)
+        );
+
+        desktop.close_window(primary);
+        desktop.pump(&mut app);
+
+        assert_eq!(app.workspace_manager.workspace_for_window(primary), None);
+        assert!(app.workspace_manager.workspace_container.workspaces()[0]
+            .windows
+            .is_empty());
+    }
+
+    #[test]
+    fn switching_workspace_hides_and_shows_windows() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| desktop.open_window(0, "Notepad", "Notepad", "notepad.exe"))
+            .collect();
+        desktop.focus(handles[1]);
+        desktop.press(alt(VirtualKey::Key3));
+        desktop.pump(&mut app);
+
+        assert_eq!(app.workspace_manager.focused_workspace(), Some(2));
+        assert!(handles.iter().all(|&h| !desktop.is_visible(h)));
+
+        desktop.press(alt(VirtualKey::Key1));
+        desktop.pump(&mut app);
+
+        assert_eq!(app.workspace_manager.focused_workspace(), Some(0));
+        assert!(handles.iter().all(|&h| desktop.is_visible(h)));
+        assert_eq!(app.windows_manager.windows.len(), 2);
+    }
+
+    #[test]
+    fn switching_to_visible_workspace_swaps_monitors() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let primary = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        let secondary = desktop.open_window(1, "Notepad", "Notepad", "notepad.exe");
+        desktop.focus(primary);
+        desktop.press(alt(VirtualKey::Key2));
+        desktop.pump(&mut app);
+
+        let container = &app.workspace_manager.workspace_container;
+        let monitors = app.workspace_manager.monitor_container.get_all_monitors();
+
+        assert_eq!(container.get_workspace_for_monitor(&monitors[0]), Some(1));
+        assert_eq!(container.get_workspace_for_monitor(&monitors[1]), Some(0));
+        assert!(desktop.is_visible(primary));
+        assert!(desktop.is_visible(secondary));
+    }
+
+    #[test]
+    fn moves_focused_window_to_workspace() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let moved = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        let kept = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.focus(moved);
+        desktop.press(alt_shift(VirtualKey::Key4));
+        desktop.pump(&mut app);
+
+        assert_eq!(app.workspace_manager.workspace_for_window(moved), Some(3));
+        assert_eq!(app.workspace_manager.workspace_for_window(kept), Some(0));
+        assert!(!desktop.is_visible(moved));
+
+        desktop.press(alt(VirtualKey::Key4));
+        desktop.pump(&mut app);
+
+        assert!(desktop.is_visible(moved));
+        assert!(!desktop.is_visible(kept));
+    }
+
+    #[test]
+    fn dragging_to_another_monitor_changes_workspace() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let handle = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.pump(&mut app);
+
+        desktop.start_move(handle);
+        desktop.move_window(handle, Rectangle::new(-2000, 0, 800, 600));
+        desktop.end_move(handle);
+        desktop.pump(&mut app);
+
+        assert_eq!(app.workspace_manager.workspace_for_window(handle), Some(1));
+    }
+
+    #[test]
+    fn window_rules_place_new_windows() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+        set_layout(&mut app, LayoutEngineType::Tall);
+
+        let process = |name: &str| Some(Pattern::Exact(name.to_string()));
+        app.settings.window_rules.rules.extend([
+            WindowRule {
+                process: process("steam.exe"),
+                workspace: Some("4".to_string()),
+                ..Default::default()
+            },
+            WindowRule {
+                process: process("wt.exe"),
+                monitor: Some(1),
+                ..Default::default()
+            },
+            WindowRule {
+                title: Some(Pattern::Glob("* - Visual Studio Code".to_string())),
+                position: Some(0),
+                ..Default::default()
+            },
+            WindowRule {
+                process: process("calc.exe"),
+                size: Some(Size {
+                    width: 400,
+                    height: 600,
+                }),
+                ..Default::default()
+            },
+        ]);
+        app.apply_settings();
+
+        let notepad = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        let steam = desktop.open_window(0, "Steam", "Steam", "steam.exe");
+        let terminal =
+            desktop.open_window(0, "CASCADIA_HOSTING_WINDOW_CLASS", "Terminal", "wt.exe");
+        let code = desktop.open_window(
+            0,
+            "Chrome_WidgetWin_1",
+            "main.rs - Visual Studio Code",
+            "code.exe",
+        );
+        let calc = desktop.open_window(0, "ApplicationFrameWindow", "Calculator", "calc.exe");
+        desktop.pump(&mut app);
+
+        let workspace_for_window = |handle| app.workspace_manager.workspace_for_window(handle);
+        assert_eq!(workspace_for_window(steam), Some(3));
+        assert!(!desktop.is_visible(steam));
+        assert_eq!(workspace_for_window(terminal), Some(1));
+        assert_eq!(workspace_for_window(calc), Some(0));
+
+        // the editor takes the primary area from the window opened before it
+        assert_eq!(desktop.rect(code), Rectangle::new(0, 0, 960, 1040));
+        assert_eq!(desktop.rect(notepad), Rectangle::new(960, 0, 960, 1040));
+
+        // floated at its size in the middle of the monitor
+        assert!(app.windows_manager.floating.contains_key(&calc));
+        assert_eq!(desktop.rect(calc), Rectangle::new(760, 220, 400, 600));
+    }
+
+    #[test]
+    fn floating_windows_keep_floating_on_another_workspace() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let handle = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.focus(handle);
+        desktop.press(toggle_tiling());
+        desktop.press(alt_shift(VirtualKey::Key3));
+        desktop.pump(&mut app);
+
+        let workspace = app.workspace_manager.workspace_for_window(handle);
+        assert_eq!(workspace, Some(2));
+        let container = &app.workspace_manager.workspace_container;
+        let window = &app.windows_manager.windows[&handle];
+        assert!(container.workspaces()[2].is_floating(window));
+        assert!(app.snapshot().workspaces[2].windows[0].floating);
+
+        app.handle_action(Action::SwitchToWorkspace(2));
+        desktop.focus(handle);
+        desktop.press(toggle_tiling());
+        desktop.pump(&mut app);
+
+        let container = &app.workspace_manager.workspace_container;
+        let window = &app.windows_manager.windows[&handle];
+        assert!(!container.workspaces()[2].is_floating(window));
+        assert!(app.windows_manager.floating.is_empty());
+    }
+
+    #[test]
+    fn hidden_windows_are_shown_on_exit() {
+        let mut desktop = dual_monitor_desktop();
+        let mut app = desktop.app();
+
+        let handle = desktop.open_window(0, "Notepad", "Notepad", "notepad.exe");
+        desktop.pump(&mut app);
+        app.handle_action(Action::SwitchToWorkspace(2));
+        desktop.pump(&mut app);
+        assert!(!desktop.is_visible(handle));
+
+        app.workspace_manager.show_all_windows();
+
+        assert!(desktop.is_visible(handle));
+    }
 }